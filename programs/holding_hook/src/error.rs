@@ -0,0 +1,7 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum HoldingHookError {
+    #[msg("Not called during a token transfer")]
+    NotTransferring,
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::HolderState;
+
+/// Starts tracking a token account. Anyone can open the record, but it always starts
+/// at the current time so holding time can't be backdated.
+pub fn init_holder_state(ctx: Context<AInitHolderState>) -> Result<()> {
+    let holder_state = &mut ctx.accounts.holder_state;
+    holder_state.token_account = ctx.accounts.token_account.key();
+    holder_state.mint = ctx.accounts.token_account.mint;
+    holder_state.last_received_time = Clock::get()?.unix_timestamp as u64;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AInitHolderState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [HolderState::PREFIX_SEED, token_account.key().as_ref()],
+        bump,
+        space = 8 + HolderState::MAX_SIZE
+    )]
+    pub holder_state: Box<Account<'info, HolderState>>,
+    pub system_program: Program<'info, System>,
+}
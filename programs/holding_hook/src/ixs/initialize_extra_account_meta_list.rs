@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
+};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+use crate::HolderState;
+
+// Accounts appended to every transfer: the destination's holder state
+fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal {
+                bytes: HolderState::PREFIX_SEED.to_vec(),
+            },
+            Seed::AccountKey { index: 2 }, // Destination token account
+        ],
+        false,
+        true,
+    )?])
+}
+
+pub fn initialize_extra_account_meta_list(
+    ctx: Context<AInitializeExtraAccountMetaList>,
+) -> Result<()> {
+    let metas = extra_account_metas()?;
+    let mut data = ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?;
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas)?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AInitializeExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Validation account read by Token-2022, initialized with the TLV layout
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+        space = ExtraAccountMetaList::size_of(extra_account_metas()?.len())?,
+    )]
+    pub extra_account_meta_list: AccountInfo<'info>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+}
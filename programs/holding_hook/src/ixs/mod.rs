@@ -0,0 +1,8 @@
+pub mod initialize_extra_account_meta_list;
+pub use initialize_extra_account_meta_list::*;
+
+pub mod init_holder_state;
+pub use init_holder_state::*;
+
+pub mod transfer_hook;
+pub use transfer_hook::*;
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::spl_token_2022::{
+        extension::{
+            transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::Account as SplTokenAccount,
+    },
+    token_interface::{Mint, TokenAccount},
+};
+
+use crate::{error::HoldingHookError, HolderState};
+
+pub fn transfer_hook(ctx: Context<ATransferHook>, _amount: u64) -> Result<()> {
+    ctx.accounts.check_transferring()?;
+
+    // Token accounts without a record are not tracked, see `init_holder_state`
+    let holder_state = &ctx.accounts.holder_state;
+    if holder_state.owner != &crate::ID || holder_state.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut data = holder_state.try_borrow_mut_data()?;
+    let mut state = HolderState::try_deserialize(&mut &data[..])?;
+    state.last_received_time = Clock::get()?.unix_timestamp as u64;
+    state.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+impl<'info> ATransferHook<'info> {
+    // Only Token-2022 sets the flag, so a direct call can't reset someone's holding time
+    fn check_transferring(&self) -> Result<()> {
+        let source_token = self.source_token.to_account_info();
+        let data = source_token.try_borrow_data()?;
+        let account = StateWithExtensions::<SplTokenAccount>::unpack(&data)?;
+        let extension = account.get_extension::<TransferHookAccount>()?;
+        require!(
+            bool::from(extension.transferring),
+            HoldingHookError::NotTransferring
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ATransferHook<'info> {
+    #[account(token::mint = mint)]
+    pub source_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(token::mint = mint)]
+    pub destination_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Source token account owner or delegate
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: Validation account, checked by Token-2022
+    #[account(
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    /// CHECK: Destination holder state, may not be initialized
+    #[account(
+        mut,
+        seeds = [HolderState::PREFIX_SEED, destination_token.key().as_ref()],
+        bump,
+    )]
+    pub holder_state: UncheckedAccount<'info>,
+}
@@ -0,0 +1,49 @@
+#![allow(unused)]
+
+use anchor_lang::prelude::*;
+use spl_transfer_hook_interface::instruction::TransferHookInstruction;
+
+pub mod error;
+pub mod state;
+
+pub mod ixs;
+
+use ixs::*;
+use state::*;
+
+declare_id!("FKsXTsSdJuftfU8vwgKLwiKei2kvJTeZh9NKra9Qatno");
+
+/// Token-2022 transfer hook recording when each token account last received tokens,
+/// so holding time based taxes don't depend on seller supplied timestamps.
+#[program]
+pub mod holding_hook {
+    use super::*;
+
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<AInitializeExtraAccountMetaList>,
+    ) -> Result<()> {
+        ixs::initialize_extra_account_meta_list(ctx)
+    }
+
+    pub fn init_holder_state(ctx: Context<AInitHolderState>) -> Result<()> {
+        ixs::init_holder_state(ctx)
+    }
+
+    pub fn transfer_hook(ctx: Context<ATransferHook>, amount: u64) -> Result<()> {
+        ixs::transfer_hook(ctx, amount)
+    }
+
+    // Token-2022 calls the hook with the transfer hook interface discriminator
+    pub fn fallback<'info>(
+        program_id: &Pubkey,
+        accounts: &'info [AccountInfo<'info>],
+        data: &[u8],
+    ) -> Result<()> {
+        match TransferHookInstruction::unpack(data)? {
+            TransferHookInstruction::Execute { amount } => {
+                __private::__global::transfer_hook(program_id, accounts, &amount.to_le_bytes())
+            }
+            _ => Err(ProgramError::InvalidInstructionData.into()),
+        }
+    }
+}
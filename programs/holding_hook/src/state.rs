@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct HolderState {
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub last_received_time: u64, // Updated by the transfer hook on every inbound transfer
+}
+
+impl HolderState {
+    pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
+    pub const PREFIX_SEED: &'static [u8] = b"holder";
+}
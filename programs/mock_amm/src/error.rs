@@ -0,0 +1,10 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum MockAmmError {
+    #[msg("Both sides of the pool need liquidity")]
+    InsufficientLiquidity,
+
+    #[msg("Math Overflow")]
+    MathOverflow,
+}
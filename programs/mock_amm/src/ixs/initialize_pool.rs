@@ -0,0 +1,156 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, MintTo, Token, TokenAccount},
+    token_interface::{self, TokenInterface, TransferChecked},
+};
+
+use crate::{error::MockAmmError, AmmPool};
+
+/// Creates the pool for `base_mint` with the creator's tokens and SOL, minting
+/// `sqrt(base * quote)` LP tokens to the LP owner.
+pub fn initialize_pool<'info>(
+    ctx: Context<'_, '_, '_, 'info, AInitializePool<'info>>,
+    base_amount: u64,
+    quote_amount: u64,
+) -> Result<()> {
+    require!(
+        base_amount > 0 && quote_amount > 0,
+        MockAmmError::InsufficientLiquidity
+    );
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.base_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.creator_base_account.to_account_info(),
+                mint: ctx.accounts.base_mint.to_account_info(),
+                to: ctx.accounts.base_vault.to_account_info(),
+                authority: ctx.accounts.token_authority.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        base_amount,
+        ctx.accounts.base_mint.decimals,
+    )?;
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.creator.to_account_info(),
+                to: ctx.accounts.amm_pool.to_account_info(),
+            },
+        ),
+        quote_amount,
+    )?;
+
+    let liquidity = integer_sqrt((base_amount as u128) * (quote_amount as u128));
+    let liquidity = u64::try_from(liquidity).map_err(|_| MockAmmError::MathOverflow)?;
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                to: ctx.accounts.lp_account.to_account_info(),
+                authority: ctx.accounts.amm_pool.to_account_info(),
+            },
+            &[&[
+                AmmPool::PREFIX_SEED,
+                ctx.accounts.base_mint.to_account_info().key.as_ref(),
+                &[ctx.bumps.amm_pool],
+            ]],
+        ),
+        liquidity,
+    )?;
+
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    amm_pool.base_mint = ctx.accounts.base_mint.key();
+    amm_pool.lp_mint = ctx.accounts.lp_mint.key();
+    amm_pool.base_vault = ctx.accounts.base_vault.key();
+    amm_pool.base_reserve = base_amount;
+    amm_pool.quote_reserve = quote_amount;
+    Ok(())
+}
+
+// Newton's method, rounding down
+fn integer_sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value / 2 + 1;
+    let mut y = (x + value / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+#[derive(Accounts)]
+pub struct AInitializePool<'info> {
+    /// Pays the rent of the new accounts
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Provides the SOL side of the liquidity
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// Owner of `creator_base_account`
+    pub token_authority: Signer<'info>,
+
+    /// CHECK: Receives the LP tokens
+    pub lp_owner: UncheckedAccount<'info>,
+
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [AmmPool::PREFIX_SEED, base_mint.key().as_ref()],
+        bump,
+        space = 8 + AmmPool::MAX_SIZE
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [AmmPool::LP_MINT_SEED, amm_pool.key().as_ref()],
+        bump,
+        mint::decimals = AmmPool::LP_DECIMALS,
+        mint::authority = amm_pool,
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = base_mint,
+        associated_token::authority = amm_pool,
+        associated_token::token_program = base_token_program,
+    )]
+    pub base_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = base_mint,
+        token::authority = token_authority,
+        token::token_program = base_token_program,
+    )]
+    pub creator_base_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = lp_mint,
+        associated_token::authority = lp_owner,
+    )]
+    pub lp_account: Box<Account<'info, TokenAccount>>,
+
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,2 @@
+pub mod initialize_pool;
+pub use initialize_pool::*;
@@ -0,0 +1,28 @@
+#![allow(unused)]
+
+use anchor_lang::prelude::*;
+
+pub mod error;
+pub mod state;
+
+pub mod ixs;
+
+use ixs::*;
+use state::*;
+
+declare_id!("GUab1JHvEXNddNw7mSkM1daYX1s6ugKZwKGyoqxpUsSU");
+
+/// Minimal constant-product AMM standing in for an external AMM in local tests,
+/// so graduated pools can be migrated to it through `thrust_app::migrate`.
+#[program]
+pub mod mock_amm {
+    use super::*;
+
+    pub fn initialize_pool<'info>(
+        ctx: Context<'_, '_, '_, 'info, AInitializePool<'info>>,
+        base_amount: u64,
+        quote_amount: u64,
+    ) -> Result<()> {
+        ixs::initialize_pool(ctx, base_amount, quote_amount)
+    }
+}
@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+/// Token/SOL pool. The SOL side is held as lamports of this account.
+#[account]
+pub struct AmmPool {
+    pub base_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+}
+
+impl AmmPool {
+    pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
+    pub const PREFIX_SEED: &'static [u8] = b"amm_pool";
+    pub const LP_MINT_SEED: &'static [u8] = b"lp_mint";
+    pub const LP_DECIMALS: u8 = 9;
+}
@@ -0,0 +1,156 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, MintTo, Token},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use crate::{
+    constants::RESERVE_SEED, error::ThrustAppError, utils::transfer_tokens, LiquidityEvent,
+    PoolAmm, PoolState,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AddLiquidityInput {
+    pub lp_amount: u64,        // LP tokens to mint
+    pub max_base_amount: u64,  // Most tokens the deposit may take
+    pub max_quote_amount: u64, // Most SOL the deposit may take
+}
+
+/// Deposits tokens and SOL into a graduated pool's built-in AMM for LP tokens.
+pub fn add_liquidity<'info>(
+    ctx: Context<'_, '_, '_, 'info, AAddLiquidity<'info>>,
+    input: AddLiquidityInput,
+) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+    let mut amm = pool_state.amm.ok_or(ThrustAppError::AmmNotActive)?;
+    let (base_amount, quote_amount) = amm.add_liquidity(input.lp_amount)?;
+    require!(
+        base_amount <= input.max_base_amount && quote_amount <= input.max_quote_amount,
+        ThrustAppError::SlippageExceeded
+    );
+    pool_state.amm = Some(amm);
+
+    transfer_tokens(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.user_base_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.reserver_base_ata.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        base_amount,
+        ctx.accounts.mint.decimals,
+    )?;
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.reserve_pda.to_account_info(),
+            },
+        ),
+        quote_amount,
+    )?;
+
+    let mint_key = ctx.accounts.mint.key();
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.lp_token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                to: ctx.accounts.user_lp_ata.to_account_info(),
+                authority: pool_state.to_account_info(),
+            },
+            &[&[
+                PoolState::PREFIX_SEED,
+                mint_key.as_ref(),
+                &[ctx.bumps.pool_state],
+            ]],
+        ),
+        input.lp_amount,
+    )?;
+
+    emit!(LiquidityEvent {
+        user: ctx.accounts.user.key(),
+        mint: mint_key,
+        base_amount,
+        quote_amount,
+        lp_amount: input.lp_amount,
+        is_add: true,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AAddLiquidity<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            PoolState::PREFIX_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Created by the first deposit
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [PoolAmm::LP_MINT_SEED, mint.key().as_ref()],
+        bump,
+        mint::decimals = PoolAmm::LP_DECIMALS,
+        mint::authority = pool_state,
+        mint::token_program = lp_token_program,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [
+            RESERVE_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = pool_state,
+    )]
+    pub reserver_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = user,
+    )]
+    pub user_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = lp_mint,
+        associated_token::token_program = lp_token_program,
+        associated_token::authority = user,
+    )]
+    pub user_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub lp_token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
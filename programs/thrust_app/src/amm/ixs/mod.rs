@@ -0,0 +1,8 @@
+pub mod swap;
+pub use swap::*;
+
+pub mod add_liquidity;
+pub use add_liquidity::*;
+
+pub mod remove_liquidity;
+pub use remove_liquidity::*;
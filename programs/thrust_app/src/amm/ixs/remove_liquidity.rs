@@ -0,0 +1,152 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Burn, Token},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use crate::{
+    constants::RESERVE_SEED, error::ThrustAppError, utils::transfer_tokens, LiquidityEvent,
+    PoolAmm, PoolState,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RemoveLiquidityInput {
+    pub lp_amount: u64,        // LP tokens to burn
+    pub min_base_amount: u64,  // Fewest tokens to receive
+    pub min_quote_amount: u64, // Least SOL to receive
+}
+
+/// Burns LP tokens of a graduated pool's built-in AMM for their share of the reserves.
+pub fn remove_liquidity<'info>(
+    ctx: Context<'_, '_, '_, 'info, ARemoveLiquidity<'info>>,
+    input: RemoveLiquidityInput,
+) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+    let mut amm = pool_state.amm.ok_or(ThrustAppError::AmmNotActive)?;
+    let (base_amount, quote_amount) = amm.remove_liquidity(input.lp_amount)?;
+    require!(
+        base_amount >= input.min_base_amount && quote_amount >= input.min_quote_amount,
+        ThrustAppError::SlippageExceeded
+    );
+    pool_state.amm = Some(amm);
+
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.lp_token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                from: ctx.accounts.user_lp_ata.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        input.lp_amount,
+    )?;
+
+    let mint_key = ctx.accounts.mint.key();
+    transfer_tokens(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.reserver_base_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.user_base_ata.to_account_info(),
+                authority: pool_state.to_account_info(),
+            },
+            &[&[
+                PoolState::PREFIX_SEED,
+                mint_key.as_ref(),
+                &[ctx.bumps.pool_state],
+            ]],
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        base_amount,
+        ctx.accounts.mint.decimals,
+    )?;
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.reserve_pda.to_account_info(),
+                to: ctx.accounts.user.to_account_info(),
+            },
+            &[&[RESERVE_SEED, mint_key.as_ref(), &[ctx.bumps.reserve_pda]]],
+        ),
+        quote_amount,
+    )?;
+
+    emit!(LiquidityEvent {
+        user: ctx.accounts.user.key(),
+        mint: mint_key,
+        base_amount,
+        quote_amount,
+        lp_amount: input.lp_amount,
+        is_add: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ARemoveLiquidity<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            PoolState::PREFIX_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [PoolAmm::LP_MINT_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [
+            RESERVE_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = pool_state,
+    )]
+    pub reserver_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = user,
+    )]
+    pub user_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = lp_mint,
+        associated_token::token_program = lp_token_program,
+        associated_token::authority = user,
+    )]
+    pub user_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub lp_token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
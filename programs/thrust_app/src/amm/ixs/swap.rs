@@ -0,0 +1,150 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use crate::{
+    constants::RESERVE_SEED, error::ThrustAppError, utils::transfer_tokens, PoolState, SwapEvent,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SwapInput {
+    pub amount_in: u64,      // SOL spent on buys, tokens sold on sells
+    pub min_amount_out: u64, // Tokens received on buys, SOL received on sells
+    pub is_buy: bool,
+}
+
+/// Trades a graduated pool on its built-in AMM, see `PoolAmm::swap`.
+pub fn swap<'info>(ctx: Context<'_, '_, '_, 'info, ASwap<'info>>, input: SwapInput) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+    let mut amm = pool_state.amm.ok_or(ThrustAppError::AmmNotActive)?;
+    let (amount_out, fee) = amm.swap(input.amount_in, input.is_buy)?;
+    require!(
+        amount_out >= input.min_amount_out,
+        ThrustAppError::SlippageExceeded
+    );
+    pool_state.amm = Some(amm);
+
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let mint_key = ctx.accounts.mint.key();
+    if input.is_buy {
+        system_program::transfer(
+            CpiContext::new(
+                system_program,
+                system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.reserve_pda.to_account_info(),
+                },
+            ),
+            input.amount_in,
+        )?;
+        transfer_tokens(
+            CpiContext::new_with_signer(
+                token_program,
+                TransferChecked {
+                    from: ctx.accounts.reserver_base_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.user_base_ata.to_account_info(),
+                    authority: pool_state.to_account_info(),
+                },
+                &[&[
+                    PoolState::PREFIX_SEED,
+                    mint_key.as_ref(),
+                    &[ctx.bumps.pool_state],
+                ]],
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            amount_out,
+            ctx.accounts.mint.decimals,
+        )?;
+    } else {
+        transfer_tokens(
+            CpiContext::new(
+                token_program,
+                TransferChecked {
+                    from: ctx.accounts.user_base_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.reserver_base_ata.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            input.amount_in,
+            ctx.accounts.mint.decimals,
+        )?;
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                system_program,
+                system_program::Transfer {
+                    from: ctx.accounts.reserve_pda.to_account_info(),
+                    to: ctx.accounts.user.to_account_info(),
+                },
+                &[&[RESERVE_SEED, mint_key.as_ref(), &[ctx.bumps.reserve_pda]]],
+            ),
+            amount_out,
+        )?;
+    }
+
+    emit!(SwapEvent {
+        user: ctx.accounts.user.key(),
+        mint: mint_key,
+        amount_in: input.amount_in,
+        amount_out,
+        fee,
+        base_reserve: amm.base_reserve,
+        quote_reserve: amm.quote_reserve,
+        is_buy: input.is_buy,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ASwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            PoolState::PREFIX_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [
+            RESERVE_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = pool_state,
+    )]
+    pub reserver_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = user,
+    )]
+    pub user_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use crate::{
+    calculate_output_amount, error::ThrustAppError, isqrt, utils::calculate_trading_fee,
+};
+
+/// Constant-product pool a graduated bonding curve turns into when the built-in AMM is
+/// enabled, trading against the pool's real reserves. The liquidity the pool graduated
+/// with counts towards `lp_supply` but is never minted, so it stays locked.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct PoolAmm {
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    pub lp_supply: u64, // Minted LP tokens plus the locked graduation liquidity
+    pub fee: u64,       // Swap fee, left in the reserves for liquidity providers
+}
+
+impl PoolAmm {
+    pub const LP_MINT_SEED: &'static [u8] = b"lp_mint";
+    pub const LP_DECIMALS: u8 = 9;
+
+    pub fn new(base_reserve: u64, quote_reserve: u64, fee: u64) -> Result<Self> {
+        require!(
+            base_reserve > 0 && quote_reserve > 0,
+            ThrustAppError::InsufficientLiquidity
+        );
+        let lp_supply = isqrt((base_reserve as u128) * (quote_reserve as u128));
+        Ok(Self {
+            base_reserve,
+            quote_reserve,
+            lp_supply: u64::try_from(lp_supply).map_err(|_| ThrustAppError::MathOverflow)?,
+            fee,
+        })
+    }
+
+    /// Swaps `amount_in` SOL for tokens when `is_buy`, tokens for SOL otherwise.
+    /// Returns the output amount and the fee charged on the input.
+    pub fn swap(&mut self, amount_in: u64, is_buy: bool) -> Result<(u64, u64)> {
+        let fee = calculate_trading_fee(self.fee, amount_in)?;
+        let (input_reserve, output_reserve) = if is_buy {
+            (self.quote_reserve, self.base_reserve)
+        } else {
+            (self.base_reserve, self.quote_reserve)
+        };
+        let amount_out = calculate_output_amount(amount_in - fee, input_reserve, output_reserve)?;
+        require!(amount_out > 0, ThrustAppError::ZeroOutput);
+        require!(
+            amount_out < output_reserve,
+            ThrustAppError::InsufficientLiquidity
+        );
+
+        let input_reserve = input_reserve
+            .checked_add(amount_in)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        let output_reserve = output_reserve - amount_out;
+        if is_buy {
+            (self.quote_reserve, self.base_reserve) = (input_reserve, output_reserve);
+        } else {
+            (self.base_reserve, self.quote_reserve) = (input_reserve, output_reserve);
+        }
+        Ok((amount_out, fee))
+    }
+
+    /// Mints `lp_amount` against a deposit of both sides at the pool's ratio, rounded up.
+    /// Returns the token and SOL amounts to deposit.
+    pub fn add_liquidity(&mut self, lp_amount: u64) -> Result<(u64, u64)> {
+        require!(lp_amount > 0, ThrustAppError::ZeroOutput);
+        let base_amount = share_of(self.base_reserve, lp_amount, self.lp_supply, true)?;
+        let quote_amount = share_of(self.quote_reserve, lp_amount, self.lp_supply, true)?;
+        self.base_reserve = self
+            .base_reserve
+            .checked_add(base_amount)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        self.quote_reserve = self
+            .quote_reserve
+            .checked_add(quote_amount)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        self.lp_supply = self
+            .lp_supply
+            .checked_add(lp_amount)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        Ok((base_amount, quote_amount))
+    }
+
+    /// Burns `lp_amount` for its share of both reserves, rounded down.
+    /// Returns the token and SOL amounts to pay out.
+    pub fn remove_liquidity(&mut self, lp_amount: u64) -> Result<(u64, u64)> {
+        require!(lp_amount > 0, ThrustAppError::ZeroOutput);
+        let base_amount = share_of(self.base_reserve, lp_amount, self.lp_supply, false)?;
+        let quote_amount = share_of(self.quote_reserve, lp_amount, self.lp_supply, false)?;
+        self.base_reserve -= base_amount;
+        self.quote_reserve -= quote_amount;
+        self.lp_supply = self
+            .lp_supply
+            .checked_sub(lp_amount)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        Ok((base_amount, quote_amount))
+    }
+}
+
+// `reserve * lp_amount / lp_supply`, rounded up when depositing so LPs never gain on entry
+fn share_of(reserve: u64, lp_amount: u64, lp_supply: u64, round_up: bool) -> Result<u64> {
+    let numerator = (reserve as u128) * (lp_amount as u128);
+    let denominator = lp_supply as u128;
+    let share = if round_up {
+        numerator
+            .checked_add(denominator - 1)
+            .ok_or(ThrustAppError::MathOverflow)?
+            / denominator
+    } else {
+        numerator / denominator
+    };
+    u64::try_from(share).map_err(|_| ThrustAppError::MathOverflow.into())
+}
@@ -4,9 +4,23 @@ use anchor_lang::solana_program::pubkey::Pubkey;
 
 pub const NATIVE_MINT_STR: &'static str = "So11111111111111111111111111111111111111112"; //TODO:
 pub const FEE_PER_DIV: u128 = 1000;
+pub const PRICE_PRECISION: u128 = 1_000_000_000;
+pub const CURVE_PRECISION: u128 = 1_000_000_000_000_000_000;
 
 pub const TOTAL_SUPPLY: u64 = 1_000_000_000_000_000; // 1 billion
-pub const GRADUATE_FEE: u64 = 5_000_000_000; // 5 SOL
+pub const TOKEN_DECIMALS: u8 = 6;
+pub const MIN_TOKEN_SUPPLY: u64 = 1_000_000_000_000; // default lower bound: 1 million at 6 decimals
+pub const MAX_TOKEN_SUPPLY: u64 = 10_000_000_000_000_000_000; // default upper bound: 10 billion at 9 decimals
+pub const MAX_TOKEN_DECIMALS: u8 = 9;
+pub const GRADUATE_FEE: u64 = 5_000_000_000; // default: 5 SOL
 pub const VIRT_SOL_RESERVE: u64 = 24_000_000_000; // 24 SOL
-pub const REAL_SOL_THRESHOLD: u64 = 100_000_000_000; // 95 + 5 SOL (GRADUATE_FEE) calculated at $200 sol price
+pub const REAL_SOL_THRESHOLD: u64 = 100_000_000_000; // default: 95 + 5 SOL (GRADUATE_FEE) calculated at $200 sol price
+pub const MIN_REAL_SOL_THRESHOLD: u64 = 50_000_000_000; // default lower bound: 50 SOL
+pub const MAX_REAL_SOL_THRESHOLD: u64 = 500_000_000_000; // default upper bound: 500 SOL
+pub const CREATOR_FEE_SHARE: u64 = 0; // default: creators get no share unless they pick one
+pub const MAX_CREATOR_FEE_SHARE: u64 = 50_000; // default upper bound: 50% of the trading fee
+pub const MAX_INTEGRATOR_FEE: u64 = 1_000; // default upper bound: 1% of the trade
+pub const AMM_FEE: u64 = 250; // default: 0.25% of each swap on the built-in AMM
+pub const GRADUATION_BOUNTY_SHARE: u64 = 2_000; // default: 2% of the graduation fee to the keeper
 pub const RESERVE_SEED: &'static [u8] = b"reserve";
+pub const CREATOR_VAULT_SEED: &[u8] = b"creator_vault";
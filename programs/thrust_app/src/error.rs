@@ -46,4 +46,124 @@ pub enum ThrustAppError {
 
     #[msg("Exceeds buy limit")]
     ExceedsWalletLimit,
+
+    #[msg("Slippage tolerance exceeded")]
+    SlippageExceeded,
+
+    #[msg("Amount exceeds graduation threshold")]
+    ThresholdExceeded,
+
+    #[msg("Invalid bonding curve parameters")]
+    InvalidCurve,
+
+    #[msg("Math overflow")]
+    MathOverflow,
+
+    #[msg("Insufficient liquidity in pool")]
+    InsufficientLiquidity,
+
+    #[msg("Trade output is zero")]
+    ZeroOutput,
+
+    #[msg("Pool invariant violated")]
+    InvariantViolation,
+
+    #[msg("Graduation threshold out of bounds")]
+    InvalidThreshold,
+
+    #[msg("Invalid graduation mode")]
+    InvalidGraduationMode,
+
+    #[msg("Sol price is not set")]
+    SolPriceNotSet,
+
+    #[msg("Invalid launch profile")]
+    InvalidLaunchProfile,
+
+    #[msg("Launch profile is disabled")]
+    LaunchProfileDisabled,
+
+    #[msg("Tax type not allowed by launch profile")]
+    TaxTypeNotAllowed,
+
+    #[msg("Token supply out of bounds")]
+    InvalidTokenSupply,
+
+    #[msg("Token decimals out of bounds")]
+    InvalidTokenDecimals,
+
+    #[msg("Holder state required for hook tracked pools")]
+    HolderStateRequired,
+
+    #[msg("Holding hook accounts required for decay tax pools")]
+    HoldingHookAccountsRequired,
+
+    #[msg("Reserve and creator token account required for the initial buy")]
+    InitialBuyAccountsRequired,
+
+    #[msg("Invalid fee share")]
+    InvalidFeeShare,
+
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+
+    #[msg("Invalid fee tiers")]
+    InvalidFeeTiers,
+
+    #[msg("Invalid fee override")]
+    InvalidFeeOverride,
+
+    #[msg("Integrator fee above the allowed maximum")]
+    InvalidIntegratorFee,
+
+    #[msg("Integrator account required to charge an integrator fee")]
+    IntegratorRequired,
+
+    #[msg("Invalid fee splits")]
+    InvalidFeeSplits,
+
+    #[msg("Fee split recipient account missing")]
+    FeeRecipientMissing,
+
+    #[msg("Users can't refer themselves")]
+    SelfReferral,
+
+    #[msg("Referrer was referred by this user")]
+    ReferralCycle,
+
+    #[msg("Referrer's user state required to bind a referrer")]
+    ReferrerUserStateRequired,
+
+    #[msg("Referral codes are 3 to 16 lowercase letters, digits or underscores")]
+    InvalidReferralCode,
+
+    #[msg("Referrer state required for trades through a referral code")]
+    ReferrerStateRequired,
+
+    #[msg("Invalid referral tiers")]
+    InvalidReferralTiers,
+
+    #[msg("Pool trades on the built-in AMM")]
+    AmmActive,
+
+    #[msg("Pool has no built-in AMM")]
+    AmmNotActive,
+
+    #[msg("Invalid AMM fee")]
+    InvalidAmmFee,
+
+    #[msg("Decay tax needs a Token-2022 pool with the holding hook")]
+    DecayTaxRequiresHoldingHook,
+
+    #[msg("No AMM configured for migrations")]
+    AmmNotConfigured,
+
+    #[msg("AMM program does not match the configured AMM")]
+    InvalidAmmProgram,
+
+    #[msg("Invalid AMM pool creation accounts")]
+    InvalidAmmInitAccounts,
+
+    #[msg("Account already has the current layout")]
+    AccountUpToDate,
 }
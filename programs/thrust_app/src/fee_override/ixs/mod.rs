@@ -0,0 +1,5 @@
+pub mod set_fee_override;
+pub use set_fee_override::*;
+
+pub mod remove_fee_override;
+pub use remove_fee_override::*;
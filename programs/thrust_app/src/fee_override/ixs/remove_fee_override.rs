@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+use crate::{error::ThrustAppError, FeeOverride, MainState};
+
+pub fn remove_fee_override(ctx: Context<ARemoveFeeOverride>) -> Result<()> {
+    // Closing the account returns the wallet to the regular fees
+    msg!("Removed fee override for {}", ctx.accounts.fee_override.wallet);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ARemoveFeeOverride<'info> {
+    #[account(mut, address = main_state.owner @ ThrustAppError::Unauthorised)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+        has_one = owner,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [FeeOverride::PREFIX_SEED, fee_override.wallet.as_ref()],
+        bump,
+    )]
+    pub fee_override: Box<Account<'info, FeeOverride>>,
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::{error::ThrustAppError, FeeOverride, MainState};
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug)]
+pub struct FeeOverrideInput {
+    pub wallet: Pubkey, // Wallet the fee applies to, also the PDA seed
+    pub trading_fee: u64,
+    pub tax_exempt: bool,
+}
+
+pub fn set_fee_override(ctx: Context<ASetFeeOverride>, input: FeeOverrideInput) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(
+        main_state.initialized.eq(&true),
+        ThrustAppError::Uninitialized
+    );
+
+    let fee_override = &mut ctx.accounts.fee_override;
+    fee_override.wallet = input.wallet;
+    fee_override.trading_fee = input.trading_fee;
+    fee_override.tax_exempt = input.tax_exempt;
+    fee_override.validate()?;
+
+    msg!("Set fee override for {}", input.wallet);
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(input: FeeOverrideInput)]
+pub struct ASetFeeOverride<'info> {
+    #[account(mut, address = main_state.owner @ ThrustAppError::Unauthorised)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+        has_one = owner,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [FeeOverride::PREFIX_SEED, input.wallet.as_ref()],
+        bump,
+        space = 8 + FeeOverride::MAX_SIZE
+    )]
+    pub fee_override: Box<Account<'info, FeeOverride>>,
+    pub system_program: Program<'info, System>,
+}
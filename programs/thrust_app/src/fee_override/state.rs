@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+use crate::{constants::FEE_PER_DIV, error::ThrustAppError};
+
+/// Admin managed trading fee negotiated with a wallet, such as a market maker or a
+/// partner app. Trades that pass it pay this fee in place of the pool's trading fee
+/// and volume tiers.
+#[account]
+#[derive(Default)]
+pub struct FeeOverride {
+    pub wallet: Pubkey,
+    pub trading_fee: u64,
+    pub tax_exempt: bool, // Sells skip the pool's sell tax and pay `trading_fee` only
+}
+
+impl FeeOverride {
+    pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
+    pub const PREFIX_SEED: &'static [u8] = b"fee_override";
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            (self.trading_fee as u128) <= FEE_PER_DIV * 100,
+            ThrustAppError::InvalidFeeOverride
+        );
+        Ok(())
+    }
+}
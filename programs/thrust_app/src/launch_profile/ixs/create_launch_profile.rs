@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use crate::{error::ThrustAppError, CurveType, LaunchProfile, MainState, WaitingRoomConfig};
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug)]
+pub struct LaunchProfileInput {
+    pub name: [u8; 32], // Profile name, also the PDA seed
+    pub enabled: bool,
+    pub curve_type: CurveType,
+    pub init_real_base_reserves: u64,
+    pub init_virt_quote_reserves: u64,
+    pub real_sol_threshold: u64,
+    pub graduate_fee: u64,
+    pub trading_fee: Option<u64>,
+    pub allowed_tax_types: u8,
+    pub waiting_room_config: Option<WaitingRoomConfig>,
+}
+
+pub fn create_launch_profile(
+    ctx: Context<ACreateLaunchProfile>,
+    input: LaunchProfileInput,
+) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(
+        main_state.initialized.eq(&true),
+        ThrustAppError::Uninitialized
+    );
+
+    let launch_profile = &mut ctx.accounts.launch_profile;
+    launch_profile.apply(input);
+    launch_profile.validate(main_state)?;
+
+    msg!("Created launch profile");
+    Ok(())
+}
+
+impl LaunchProfile {
+    pub fn apply(&mut self, input: LaunchProfileInput) {
+        self.name = input.name;
+        self.enabled = input.enabled;
+        self.curve_type = input.curve_type;
+        self.init_real_base_reserves = input.init_real_base_reserves;
+        self.init_virt_quote_reserves = input.init_virt_quote_reserves;
+        self.real_sol_threshold = input.real_sol_threshold;
+        self.graduate_fee = input.graduate_fee;
+        self.trading_fee = input.trading_fee;
+        self.allowed_tax_types = input.allowed_tax_types;
+        self.waiting_room_config = input.waiting_room_config;
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(input: LaunchProfileInput)]
+pub struct ACreateLaunchProfile<'info> {
+    #[account(mut, address = main_state.owner @ ThrustAppError::Unauthorised)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+        has_one = owner,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [LaunchProfile::PREFIX_SEED, input.name.as_ref()],
+        bump,
+        space = 8 + LaunchProfile::MAX_SIZE
+    )]
+    pub launch_profile: Box<Account<'info, LaunchProfile>>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,5 @@
+pub mod create_launch_profile;
+pub use create_launch_profile::*;
+
+pub mod update_launch_profile;
+pub use update_launch_profile::*;
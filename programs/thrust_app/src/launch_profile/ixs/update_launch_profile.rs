@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::{error::ThrustAppError, LaunchProfile, LaunchProfileInput, MainState};
+
+pub fn update_launch_profile(
+    ctx: Context<AUpdateLaunchProfile>,
+    input: LaunchProfileInput,
+) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(
+        main_state.initialized.eq(&true),
+        ThrustAppError::Uninitialized
+    );
+
+    // Pools already created keep the parameters they were launched with
+    let launch_profile = &mut ctx.accounts.launch_profile;
+    launch_profile.apply(input);
+    launch_profile.validate(main_state)?;
+
+    msg!("Updated launch profile");
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(input: LaunchProfileInput)]
+pub struct AUpdateLaunchProfile<'info> {
+    #[account(mut, address = main_state.owner @ ThrustAppError::Unauthorised)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+        has_one = owner,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        mut,
+        seeds = [LaunchProfile::PREFIX_SEED, input.name.as_ref()],
+        bump,
+    )]
+    pub launch_profile: Box<Account<'info, LaunchProfile>>,
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use crate::{
+    constants::FEE_PER_DIV, error::ThrustAppError, CurveType, MainState, TaxType, WaitingRoomConfig,
+};
+
+/// Admin managed preset of pool parameters that `create_pool` can reference
+/// instead of the global defaults in `MainState`.
+#[account]
+pub struct LaunchProfile {
+    pub name: [u8; 32],
+    pub enabled: bool,
+    pub curve_type: CurveType,
+    pub init_real_base_reserves: u64, // Against the main state supply, scaled to the pool's supply
+    pub init_virt_quote_reserves: u64,
+    pub real_sol_threshold: u64,
+    pub graduate_fee: u64,
+    pub trading_fee: Option<u64>, // Overrides the main state trading fee for pools of this profile
+    pub allowed_tax_types: u8,    // Bit set of `TaxType::flag`
+    pub waiting_room_config: Option<WaitingRoomConfig>, // Used when the creator sets none
+}
+
+impl LaunchProfile {
+    pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
+    pub const PREFIX_SEED: &'static [u8] = b"launch_profile";
+
+    pub fn validate(&self, main_state: &MainState) -> Result<()> {
+        require!(self.curve_type.is_valid(), ThrustAppError::InvalidCurve);
+        require!(
+            self.init_real_base_reserves > 0
+                && self.init_real_base_reserves < main_state.total_token_supply,
+            ThrustAppError::InvalidLaunchProfile
+        );
+        require!(
+            self.init_virt_quote_reserves > 0,
+            ThrustAppError::InvalidLaunchProfile
+        );
+        main_state.pool_real_sol_threshold(Some(self.real_sol_threshold))?;
+        require!(
+            self.graduate_fee < self.real_sol_threshold,
+            ThrustAppError::InvalidThreshold
+        );
+        if let Some(trading_fee) = self.trading_fee {
+            require!(
+                (trading_fee as u128) <= FEE_PER_DIV * 100,
+                ThrustAppError::InvalidLaunchProfile
+            );
+        }
+        Ok(())
+    }
+
+    pub fn allows_tax_type(&self, tax_type: &TaxType) -> bool {
+        self.allowed_tax_types & tax_type.flag() != 0
+    }
+}
@@ -2,16 +2,26 @@
 
 use anchor_lang::prelude::*;
 
+pub mod amm;
+pub mod fee_override;
+pub mod launch_profile;
 pub mod main_state;
 pub mod pool;
+pub mod referral;
+pub mod upgrade;
 pub mod user;
 
 pub mod constants;
 pub mod error;
 pub mod utils;
 
+use amm::*;
+use fee_override::*;
+use launch_profile::*;
 use main_state::*;
 use pool::*;
+use referral::*;
+use upgrade::*;
 use user::*;
 
 declare_id!("5LwAeabTKBfiFi5R9mbYwpuU7dbTwSLyHkT8u9hgnVax");
@@ -31,24 +41,149 @@ pub mod thrust_app {
         main_state::update_main_state(ctx, input)
     }
 
+    pub fn distribute_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, ADistributeFees<'info>>,
+    ) -> Result<()> {
+        main_state::distribute_fees(ctx)
+    }
+
     pub fn update_sol_price(ctx: Context<AUpdateMainState>, price: u64) -> Result<()> {
         main_state::update_sol_price(ctx, price)
     }
 
+    pub fn create_launch_profile(
+        ctx: Context<ACreateLaunchProfile>,
+        input: LaunchProfileInput,
+    ) -> Result<()> {
+        launch_profile::create_launch_profile(ctx, input)
+    }
+
+    pub fn update_launch_profile(
+        ctx: Context<AUpdateLaunchProfile>,
+        input: LaunchProfileInput,
+    ) -> Result<()> {
+        launch_profile::update_launch_profile(ctx, input)
+    }
+
+    pub fn set_fee_override(
+        ctx: Context<ASetFeeOverride>,
+        input: FeeOverrideInput,
+    ) -> Result<()> {
+        fee_override::set_fee_override(ctx, input)
+    }
+
+    pub fn remove_fee_override(ctx: Context<ARemoveFeeOverride>) -> Result<()> {
+        fee_override::remove_fee_override(ctx)
+    }
+
     pub fn create_pool(ctx: Context<ACreatePool>, input: CreatePoolInput) -> Result<()> {
         pool::create_pool(ctx, input)
     }
 
-    pub fn buy(ctx: Context<ABuy>, input: BuyInput) -> Result<()> {
-        pool::buy(ctx, input)
+    pub fn create_pool_2022<'info>(
+        ctx: Context<'_, '_, '_, 'info, ACreatePool2022<'info>>,
+        input: CreatePoolInput,
+    ) -> Result<()> {
+        pool::create_pool_2022(ctx, input)
     }
 
-    pub fn sell(ctx: Context<ASell>, input: SellInput) -> Result<()> {
+    pub fn buy<'info>(
+        ctx: Context<'_, '_, '_, 'info, ABuy<'info>>,
+        input: BuyInput,
+    ) -> Result<()> {
+        pool::buy(ctx, input)
+    }
+    pub fn buy_exact_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, ABuy<'info>>,
+        input: BuyExactOutInput,
+    ) -> Result<()> {
+        pool::buy_exact_out(ctx, input)
+    }
+    pub fn sell<'info>(
+        ctx: Context<'_, '_, '_, 'info, ASell<'info>>,
+        input: SellInput,
+    ) -> Result<()> {
         pool::sell(ctx, input)
     }
 
-    pub fn withdraw(ctx: Context<AWithdrawState>) -> Result<()> {
+    pub fn withdraw<'info>(ctx: Context<'_, '_, '_, 'info, AWithdrawState<'info>>) -> Result<()> {
         pool::withdraw(ctx)
     }
 
+    pub fn migrate<'info>(ctx: Context<'_, '_, '_, 'info, AMigrate<'info>>) -> Result<()> {
+        pool::migrate(ctx)
+    }
+
+    pub fn graduate<'info>(ctx: Context<'_, '_, '_, 'info, AGraduate<'info>>) -> Result<()> {
+        pool::graduate(ctx)
+    }
+
+    pub fn claim_creator_fees(ctx: Context<AClaimCreatorFees>) -> Result<()> {
+        pool::claim_creator_fees(ctx)
+    }
+
+    pub fn sweep_protocol_fees(ctx: Context<ASweepProtocolFees>) -> Result<()> {
+        pool::sweep_protocol_fees(ctx)
+    }
+
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, ASwap<'info>>,
+        input: SwapInput,
+    ) -> Result<()> {
+        amm::swap(ctx, input)
+    }
+
+    pub fn add_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, AAddLiquidity<'info>>,
+        input: AddLiquidityInput,
+    ) -> Result<()> {
+        amm::add_liquidity(ctx, input)
+    }
+
+    pub fn remove_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, ARemoveLiquidity<'info>>,
+        input: RemoveLiquidityInput,
+    ) -> Result<()> {
+        amm::remove_liquidity(ctx, input)
+    }
+
+    pub fn register_referrer(ctx: Context<ARegisterReferrer>) -> Result<()> {
+        referral::register_referrer(ctx)
+    }
+
+    pub fn register_referral_code(
+        ctx: Context<ARegisterReferralCode>,
+        code: [u8; 16],
+    ) -> Result<()> {
+        referral::register_referral_code(ctx, code)
+    }
+
+    pub fn claim_referral_rewards(ctx: Context<AClaimReferralRewards>) -> Result<()> {
+        referral::claim_referral_rewards(ctx)
+    }
+
+    pub fn quote_buy(ctx: Context<AQuote>, input: BuyInput) -> Result<TradeQuote> {
+        pool::quote_buy(ctx, input)
+    }
+
+    pub fn quote_sell(ctx: Context<AQuote>, input: SellInput) -> Result<TradeQuote> {
+        pool::quote_sell(ctx, input)
+    }
+
+    pub fn pool_info(ctx: Context<AQuote>) -> Result<PoolInfo> {
+        pool::pool_info(ctx)
+    }
+
+    pub fn upgrade_main_state(ctx: Context<AUpgradeMainState>) -> Result<()> {
+        upgrade::upgrade_main_state(ctx)
+    }
+
+    pub fn upgrade_pool_state(ctx: Context<AUpgradePoolState>) -> Result<()> {
+        upgrade::upgrade_pool_state(ctx)
+    }
+
+    pub fn upgrade_user_state(ctx: Context<AUpgradeUserState>) -> Result<()> {
+        upgrade::upgrade_user_state(ctx)
+    }
+
 }
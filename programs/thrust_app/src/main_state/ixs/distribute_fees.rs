@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::{error::ThrustAppError, DistributeFeesEvent, FeeVault, MainState};
+
+/// Pays the fee vault's balance out to the fee split. Anyone can call it, the recipients
+/// are passed as remaining accounts and must match `main_state.fee_splits`.
+pub fn distribute_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, ADistributeFees<'info>>,
+) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(
+        main_state.initialized.eq(&true),
+        ThrustAppError::Uninitialized
+    );
+
+    let fee_vault = &mut ctx.accounts.fee_vault;
+    let rent_exempt = Rent::get()?.minimum_balance(fee_vault.to_account_info().data_len());
+    let amount = fee_vault.get_lamports().saturating_sub(rent_exempt);
+    require!(amount > 0, ThrustAppError::NothingToClaim);
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    for (recipient, share) in main_state.fee_split_amounts(amount)? {
+        if share == 0 {
+            continue;
+        }
+        let recipient_account = ctx
+            .remaining_accounts
+            .iter()
+            .find(|account| account.key() == recipient)
+            .ok_or(ThrustAppError::FeeRecipientMissing)?;
+        // The vault is owned by the program, so lamports move without a system transfer
+        fee_vault.sub_lamports(share)?;
+        recipient_account.add_lamports(share)?;
+        fee_vault.total_distributed = fee_vault
+            .total_distributed
+            .checked_add(share)
+            .ok_or(ThrustAppError::MathOverflow)?;
+
+        emit!(DistributeFeesEvent {
+            recipient,
+            amount: share,
+            timestamp,
+        });
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ADistributeFees<'info> {
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        mut,
+        seeds = [FeeVault::PREFIX_SEED],
+        bump,
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+}
@@ -1,8 +1,13 @@
 use anchor_lang::prelude::*;
 use crate::{
-    constants::{TOTAL_SUPPLY, VIRT_SOL_RESERVE},
+    constants::{
+        AMM_FEE, CREATOR_FEE_SHARE, GRADUATE_FEE, GRADUATION_BOUNTY_SHARE, MAX_CREATOR_FEE_SHARE,
+        MAX_INTEGRATOR_FEE, MAX_REAL_SOL_THRESHOLD, MAX_TOKEN_DECIMALS, MAX_TOKEN_SUPPLY,
+        MIN_REAL_SOL_THRESHOLD, MIN_TOKEN_SUPPLY, REAL_SOL_THRESHOLD, TOKEN_DECIMALS, TOTAL_SUPPLY,
+        VIRT_SOL_RESERVE,
+    },
     error::ThrustAppError,
-    MainState,
+    FeeVault, MainState,
 };
 
 pub fn init_main_state(ctx: Context<AInitMainState>) -> Result<()> {
@@ -21,8 +26,29 @@ pub fn init_main_state(ctx: Context<AInitMainState>) -> Result<()> {
     state.init_virt_quote_reserves = VIRT_SOL_RESERVE; // default: 24 SOL
     state.trading_fee = 1_000; // default: 1%
     state.referral_reward_fee = 10_000; // default: 10% of platform fee = 0.1% of trading fee
-    state.referral_trade_limit = 100; // default value is 100. will get reward fee until 100 trades
+    state.referral_window = 0; // default: referrers earn for as long as their referees trade
     state.verify_signer_pubkey = ctx.accounts.verify_signer_pubkey.key(); // signer pubkey for verify message
+    state.real_sol_threshold = REAL_SOL_THRESHOLD; // default: 100 SOL
+    state.min_real_sol_threshold = MIN_REAL_SOL_THRESHOLD;
+    state.max_real_sol_threshold = MAX_REAL_SOL_THRESHOLD;
+    state.graduate_fee = GRADUATE_FEE; // default: 5 SOL
+    state.token_decimals = TOKEN_DECIMALS; // default: 6 decimals
+    state.max_token_decimals = MAX_TOKEN_DECIMALS;
+    state.min_token_supply = MIN_TOKEN_SUPPLY;
+    state.max_token_supply = MAX_TOKEN_SUPPLY;
+    state.creator_fee_share = CREATOR_FEE_SHARE;
+    state.max_creator_fee_share = MAX_CREATOR_FEE_SHARE;
+    state.fee_tiers = [None; 4]; // default: flat trading fee
+    state.max_integrator_fee = MAX_INTEGRATOR_FEE;
+    state.fee_splits = [None; 4]; // default: all protocol fees to the fee recipient
+    state.referral_tiers = [None; 4]; // default: flat direct cut
+    state.referral_level_fees = [0; 2]; // default: direct referrer only
+    state.amm_program = Pubkey::default(); // default: no migrations until an AMM is configured
+    state.amm_init_discriminator = [0; 8];
+    state.amm_init_accounts = [None; 16];
+    state.built_in_amm = false; // default: graduated pools are migrated
+    state.amm_fee = AMM_FEE; // default: 0.25%
+    state.graduation_bounty_share = GRADUATION_BOUNTY_SHARE; // default: 2% of the graduation fee
     Ok(())
 }
 
@@ -43,5 +69,14 @@ pub struct AInitMainState<'info> {
         space = 8 + MainState::MAX_SIZE
     )]
     pub main_state: Account<'info, MainState>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [FeeVault::PREFIX_SEED],
+        bump,
+        space = 8 + FeeVault::MAX_SIZE
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
     pub system_program: Program<'info, System>,
 }
@@ -4,3 +4,6 @@ pub use init_main_state::*;
 pub mod update_main_state_owner;
 pub use update_main_state_owner::*;
 
+pub mod distribute_fees;
+pub use distribute_fees::*;
+
@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
-use crate::{error::ThrustAppError, MainState};
+use crate::{
+    constants::FEE_PER_DIV, error::ThrustAppError, AmmInitAccount, FeeSplit, FeeTier, MainState,
+    ReferralTier,
+};
 
 #[derive(AnchorDeserialize, AnchorSerialize, Debug, Clone, Copy)]
 pub struct UpdateMainStateInput {
@@ -8,11 +11,32 @@ pub struct UpdateMainStateInput {
     trading_fee: u64,
     sol_price: u64,
     referral_reward_fee: u64,
-    referral_trade_limit: u64,
+    referral_window: u64,
     total_token_supply: Option<u64>,
     init_virt_base_reserves: Option<u64>,
     init_real_base_reserves: Option<u64>,
     init_virt_quote_reserves: Option<u64>,
+    real_sol_threshold: Option<u64>,
+    min_real_sol_threshold: Option<u64>,
+    max_real_sol_threshold: Option<u64>,
+    graduate_fee: Option<u64>,
+    token_decimals: Option<u8>,
+    max_token_decimals: Option<u8>,
+    min_token_supply: Option<u64>,
+    max_token_supply: Option<u64>,
+    creator_fee_share: Option<u64>,
+    max_creator_fee_share: Option<u64>,
+    fee_tiers: Option<[Option<FeeTier>; 4]>,
+    max_integrator_fee: Option<u64>,
+    fee_splits: Option<[Option<FeeSplit>; 4]>,
+    referral_tiers: Option<[Option<ReferralTier>; 4]>,
+    referral_level_fees: Option<[u64; 2]>,
+    amm_program: Option<Pubkey>,
+    amm_init_discriminator: Option<[u8; 8]>,
+    amm_init_accounts: Option<[Option<AmmInitAccount>; 16]>,
+    built_in_amm: Option<bool>,
+    amm_fee: Option<u64>,
+    graduation_bounty_share: Option<u64>,
 }
 
 pub fn update_main_state(
@@ -26,13 +50,13 @@ pub fn update_main_state(
     msg!("fee_recipient: {}", input.fee_recipient);
     msg!("trading_fee: {}", input.trading_fee);
     msg!("referral_reward_fee: {}", input.referral_reward_fee);
-    msg!("referral_trade_limit: {}", input.referral_trade_limit);
+    msg!("referral_window: {}", input.referral_window);
 
     state.owner = input.owner;
     state.fee_recipient = input.fee_recipient;
     state.trading_fee = input.trading_fee;
     state.referral_reward_fee = input.referral_reward_fee;
-    state.referral_trade_limit = input.referral_trade_limit;
+    state.referral_window = input.referral_window;
     state.total_token_supply = input.total_token_supply.unwrap_or(state.total_token_supply);
     state.init_virt_base_reserves = input
         .init_virt_base_reserves
@@ -43,6 +67,58 @@ pub fn update_main_state(
     state.init_virt_quote_reserves = input
         .init_virt_quote_reserves
         .unwrap_or(state.init_virt_quote_reserves);
+    state.real_sol_threshold = input.real_sol_threshold.unwrap_or(state.real_sol_threshold);
+    state.min_real_sol_threshold = input
+        .min_real_sol_threshold
+        .unwrap_or(state.min_real_sol_threshold);
+    state.max_real_sol_threshold = input
+        .max_real_sol_threshold
+        .unwrap_or(state.max_real_sol_threshold);
+    state.graduate_fee = input.graduate_fee.unwrap_or(state.graduate_fee);
+    state.token_decimals = input.token_decimals.unwrap_or(state.token_decimals);
+    state.max_token_decimals = input.max_token_decimals.unwrap_or(state.max_token_decimals);
+    state.min_token_supply = input.min_token_supply.unwrap_or(state.min_token_supply);
+    state.max_token_supply = input.max_token_supply.unwrap_or(state.max_token_supply);
+    state.creator_fee_share = input.creator_fee_share.unwrap_or(state.creator_fee_share);
+    state.max_creator_fee_share = input
+        .max_creator_fee_share
+        .unwrap_or(state.max_creator_fee_share);
+    state.referral_tiers = input.referral_tiers.unwrap_or(state.referral_tiers);
+    state.referral_level_fees = input
+        .referral_level_fees
+        .unwrap_or(state.referral_level_fees);
+    state.validate_referral_tiers()?;
+    state.built_in_amm = input.built_in_amm.unwrap_or(state.built_in_amm);
+    state.amm_fee = input.amm_fee.unwrap_or(state.amm_fee);
+    require!(
+        (state.amm_fee as u128) < FEE_PER_DIV * 100,
+        ThrustAppError::InvalidAmmFee
+    );
+    state.graduation_bounty_share = input
+        .graduation_bounty_share
+        .unwrap_or(state.graduation_bounty_share);
+    require!(
+        (state.graduation_bounty_share as u128) <= FEE_PER_DIV * 100,
+        ThrustAppError::InvalidFeeShare
+    );
+    state.validate_graduation_bounds()?;
+    state.validate_token_bounds()?;
+    state.fee_tiers = input.fee_tiers.unwrap_or(state.fee_tiers);
+    state.validate_fee_shares()?;
+    state.validate_fee_tiers()?;
+    state.max_integrator_fee = input.max_integrator_fee.unwrap_or(state.max_integrator_fee);
+    require!(
+        (state.max_integrator_fee as u128) <= FEE_PER_DIV * 100,
+        ThrustAppError::InvalidIntegratorFee
+    );
+    state.fee_splits = input.fee_splits.unwrap_or(state.fee_splits);
+    state.validate_fee_splits()?;
+    state.amm_program = input.amm_program.unwrap_or(state.amm_program);
+    state.amm_init_discriminator = input
+        .amm_init_discriminator
+        .unwrap_or(state.amm_init_discriminator);
+    state.amm_init_accounts = input.amm_init_accounts.unwrap_or(state.amm_init_accounts);
+    state.validate_amm_init_accounts()?;
     state.verify_signer_pubkey = ctx.accounts.verify_signer_pubkey.key(); // signer pubkey for verify message
     msg!("Updated mainState");
 
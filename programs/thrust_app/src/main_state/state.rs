@@ -1,4 +1,67 @@
 use anchor_lang::prelude::*;
+use crate::{
+    constants::FEE_PER_DIV, error::ThrustAppError, utils::calculate_trading_fee, UserState,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct FeeTier {
+    pub min_volume_usd: u64, // USD trading volume from which the tier applies
+    pub trading_fee: u64,    // Fee rate for traders in the tier
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct ReferralTier {
+    pub min_referred_volume: u64, // SOL volume of a referrer's referees from which the tier applies
+    pub reward_fee: u64,          // Direct referrer's cut of the trading fee in the tier
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct FeeSplit {
+    pub recipient: Pubkey, // Treasury, buyback, team or insurance fund wallet
+    pub weight: u64,       // Part of the protocol fees, the weights add up to 100%
+}
+
+/// Account passed to the pool creation instruction of the configured AMM, see
+/// `MainState::amm_init_accounts`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum AmmInitAccount {
+    Payer,                  // Migrating signer, pays the AMM accounts' rent
+    Reserve,                // Pool reserve, provides the SOL and signs
+    PoolAuthority,          // Pool state signing for the pool's tokens
+    LpOwner,                // Pool state as owner of the LP tokens, not signing
+    BaseMint,
+    ReserveBaseAccount,     // Pool's token account, provides the tokens
+    AmmPool,
+    AmmLpMint,
+    AmmBaseVault,
+    PoolLpAccount,          // Pool's LP token account, burned from after the migration
+    BaseTokenProgram,
+    LpTokenProgram,
+    AssociatedTokenProgram,
+    SystemProgram,
+}
+
+impl AmmInitAccount {
+    pub fn is_signer(&self) -> bool {
+        matches!(
+            self,
+            AmmInitAccount::Payer | AmmInitAccount::Reserve | AmmInitAccount::PoolAuthority
+        )
+    }
+
+    pub fn is_writable(&self) -> bool {
+        matches!(
+            self,
+            AmmInitAccount::Payer
+                | AmmInitAccount::Reserve
+                | AmmInitAccount::ReserveBaseAccount
+                | AmmInitAccount::AmmPool
+                | AmmInitAccount::AmmLpMint
+                | AmmInitAccount::AmmBaseVault
+                | AmmInitAccount::PoolLpAccount
+        )
+    }
+}
 
 #[account]
 pub struct MainState {
@@ -11,12 +74,270 @@ pub struct MainState {
     pub init_virt_quote_reserves: u64,
     pub trading_fee: u64,
     pub referral_reward_fee: u64,
-    pub referral_trade_limit: u64,
+    pub referral_window: u64, // Seconds referred trades pay rewards for, 0 for no limit
     pub sol_price: u64,
     pub verify_signer_pubkey: Pubkey,
+    pub real_sol_threshold: u64,     // Default graduation threshold for new pools
+    pub min_real_sol_threshold: u64, // Lowest threshold a creator may pick
+    pub max_real_sol_threshold: u64, // Highest threshold a creator may pick
+    pub graduate_fee: u64,           // Taken from the reserve when a pool graduates
+    pub token_decimals: u8,          // Default decimals for new pool mints
+    pub max_token_decimals: u8,
+    pub min_token_supply: u64,       // Bounds on the supply a creator may pick
+    pub max_token_supply: u64,
+    pub creator_fee_share: u64,      // Default part of the trading fee paid to pool creators
+    pub max_creator_fee_share: u64,  // Highest share a creator may pick
+    pub fee_tiers: [Option<FeeTier>; 4], // Ascending by volume, the highest tier reached applies
+    pub max_integrator_fee: u64,     // Highest fee an integrator may charge on top of a trade
+    pub fee_splits: [Option<FeeSplit>; 4], // Protocol fee recipients, `fee_recipient` when empty
+    pub referral_tiers: [Option<ReferralTier>; 4], // Raise the direct cut by referred volume
+    pub referral_level_fees: [u64; 2], // Cuts of the second and third referral levels
+    pub amm_program: Pubkey, // External AMM completed pools migrate to, unset until configured
+    pub amm_init_discriminator: [u8; 8], // Instruction data prefix of its pool creation
+    pub amm_init_accounts: [Option<AmmInitAccount>; 16], // Accounts its pool creation takes
+    pub built_in_amm: bool, // Graduated pools keep trading on the built-in AMM instead of migrating
+    pub amm_fee: u64,       // Swap fee of the built-in AMM
+    pub graduation_bounty_share: u64, // Part of the graduation fee paid to the migrating keeper
 }
 
 impl MainState {
     pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
     pub const PREFIX_SEED: &'static [u8] = b"main_4";
+
+    pub fn validate_graduation_bounds(&self) -> Result<()> {
+        require!(
+            self.min_real_sol_threshold <= self.real_sol_threshold
+                && self.real_sol_threshold <= self.max_real_sol_threshold,
+            ThrustAppError::InvalidThreshold
+        );
+        // The graduation fee must always be covered by the raised SOL
+        require!(
+            self.graduate_fee < self.min_real_sol_threshold,
+            ThrustAppError::InvalidThreshold
+        );
+        Ok(())
+    }
+
+    pub fn validate_token_bounds(&self) -> Result<()> {
+        self.validate_pool_token(self.total_token_supply, self.token_decimals)?;
+        // Reserve splits are configured against the default supply
+        require!(
+            self.init_real_base_reserves < self.total_token_supply,
+            ThrustAppError::InvalidTokenSupply
+        );
+        Ok(())
+    }
+
+    pub fn validate_pool_token(&self, total_supply: u64, decimals: u8) -> Result<()> {
+        require!(
+            total_supply >= self.min_token_supply && total_supply <= self.max_token_supply,
+            ThrustAppError::InvalidTokenSupply
+        );
+        require!(
+            decimals <= self.max_token_decimals,
+            ThrustAppError::InvalidTokenDecimals
+        );
+        Ok(())
+    }
+
+    pub fn validate_fee_shares(&self) -> Result<()> {
+        require!(
+            self.creator_fee_share <= self.max_creator_fee_share,
+            ThrustAppError::InvalidFeeShare
+        );
+        // Referral and creator cuts are all taken out of the same trading fee
+        let max_direct_cut = self
+            .referral_tiers
+            .iter()
+            .flatten()
+            .map(|tier| tier.reward_fee)
+            .fold(self.referral_reward_fee, u64::max);
+        let max_referral_cut = self
+            .referral_level_fees
+            .iter()
+            .fold(max_direct_cut as u128, |total, fee| total + *fee as u128);
+        require!(
+            max_referral_cut + (self.max_creator_fee_share as u128) <= FEE_PER_DIV * 100,
+            ThrustAppError::InvalidFeeShare
+        );
+        Ok(())
+    }
+
+    pub fn validate_referral_tiers(&self) -> Result<()> {
+        let count = self.referral_tiers.iter().take_while(|tier| tier.is_some()).count();
+        require!(
+            self.referral_tiers.iter().skip(count).all(|tier| tier.is_none()),
+            ThrustAppError::InvalidReferralTiers
+        );
+        let volumes = self.referral_tiers.iter().flatten().map(|tier| tier.min_referred_volume);
+        require!(
+            volumes.clone().zip(volumes.skip(1)).all(|(lower, higher)| lower < higher),
+            ThrustAppError::InvalidReferralTiers
+        );
+        Ok(())
+    }
+
+    /// Cuts of the trading fee for the direct referrer, at the tier its referred volume
+    /// reached, and for the second and third levels of the referral chain.
+    pub fn referral_rates(&self, referred_volume: u64) -> [u64; 3] {
+        let direct = self
+            .referral_tiers
+            .iter()
+            .flatten()
+            .take_while(|tier| referred_volume >= tier.min_referred_volume)
+            .last()
+            .map_or(self.referral_reward_fee, |tier| tier.reward_fee);
+        [direct, self.referral_level_fees[0], self.referral_level_fees[1]]
+    }
+
+    /// Whether the user's trades still pay their referral chain.
+    pub fn referral_window_open(&self, user_state: &UserState, current_timestamp: u64) -> bool {
+        self.referral_window == 0
+            || current_timestamp <= user_state.referred_at.saturating_add(self.referral_window)
+    }
+
+    /// Fee an integrator asked for on a trade, bounded by `max_integrator_fee`.
+    pub fn validate_integrator_fee(&self, integrator_fee: u64) -> Result<()> {
+        require!(
+            integrator_fee <= self.max_integrator_fee,
+            ThrustAppError::InvalidIntegratorFee
+        );
+        Ok(())
+    }
+
+    pub fn validate_fee_tiers(&self) -> Result<()> {
+        let mut last_volume = None;
+        let mut count = 0;
+        for tier in self.fee_tiers.iter() {
+            match tier {
+                Some(tier) => {
+                    let ascending = match last_volume {
+                        None => true,
+                        Some(last) => tier.min_volume_usd > last,
+                    };
+                    require!(
+                        ascending && (tier.trading_fee as u128) <= FEE_PER_DIV * 100,
+                        ThrustAppError::InvalidFeeTiers
+                    );
+                    last_volume = Some(tier.min_volume_usd);
+                    count += 1;
+                }
+                None => break,
+            }
+        }
+        // Tiers must be contiguous from the first slot
+        require!(
+            self.fee_tiers.iter().skip(count).all(|tier| tier.is_none()),
+            ThrustAppError::InvalidFeeTiers
+        );
+        Ok(())
+    }
+
+    pub fn validate_amm_init_accounts(&self) -> Result<()> {
+        let count = self.amm_init_accounts.iter().take_while(|account| account.is_some()).count();
+        // Accounts must be contiguous from the first slot
+        require!(
+            self.amm_init_accounts.iter().skip(count).all(|account| account.is_none()),
+            ThrustAppError::InvalidAmmInitAccounts
+        );
+        Ok(())
+    }
+
+    pub fn validate_fee_splits(&self) -> Result<()> {
+        let count = self.fee_splits.iter().take_while(|split| split.is_some()).count();
+        // Splits must be contiguous from the first slot
+        require!(
+            self.fee_splits.iter().skip(count).all(|split| split.is_none()),
+            ThrustAppError::InvalidFeeSplits
+        );
+        if count == 0 {
+            return Ok(());
+        }
+        let mut total_weight: u128 = 0;
+        for split in self.fee_splits.iter().flatten() {
+            require!(split.weight > 0, ThrustAppError::InvalidFeeSplits);
+            total_weight += split.weight as u128;
+        }
+        require!(
+            total_weight == FEE_PER_DIV * 100,
+            ThrustAppError::InvalidFeeSplits
+        );
+        Ok(())
+    }
+
+    /// Payouts of `amount` protocol fees: each split's weighted part, or everything to the
+    /// fee recipient when no split is configured. Rounding dust is left undistributed.
+    pub fn fee_split_amounts(&self, amount: u64) -> Result<Vec<(Pubkey, u64)>> {
+        if self.fee_splits.iter().all(|split| split.is_none()) {
+            return Ok(vec![(self.fee_recipient, amount)]);
+        }
+        self.fee_splits
+            .iter()
+            .flatten()
+            .map(|split| Ok((split.recipient, calculate_trading_fee(split.weight, amount)?)))
+            .collect()
+    }
+
+    /// Index and fee rate of the highest tier the user's USD trading volume reached.
+    pub fn fee_tier(&self, user_state: &UserState) -> Option<(u8, u64)> {
+        self.fee_tiers
+            .iter()
+            .flatten()
+            .enumerate()
+            .take_while(|(_, tier)| user_state.trading_volume_usd >= tier.min_volume_usd)
+            .last()
+            .map(|(index, tier)| (index as u8, tier.trading_fee))
+    }
+
+    /// Creator fee share for a new pool: the creator's pick within the bound, or the default.
+    pub fn pool_creator_fee_share(&self, requested: Option<u64>) -> Result<u64> {
+        match requested {
+            Some(share) => {
+                require!(
+                    share <= self.max_creator_fee_share,
+                    ThrustAppError::InvalidFeeShare
+                );
+                Ok(share)
+            }
+            None => Ok(self.creator_fee_share),
+        }
+    }
+
+    /// Scales an amount configured against `total_token_supply` to a pool's supply.
+    pub fn scale_to_supply(&self, amount: u64, total_supply: u64) -> Result<u64> {
+        let scaled = (amount as u128)
+            .checked_mul(total_supply as u128)
+            .ok_or(ThrustAppError::MathOverflow)?
+            .checked_div(self.total_token_supply as u128)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        u64::try_from(scaled).map_err(|_| ThrustAppError::MathOverflow.into())
+    }
+
+    /// Threshold for a new pool: the creator's pick within the admin bounds, or the default.
+    pub fn pool_real_sol_threshold(&self, requested: Option<u64>) -> Result<u64> {
+        match requested {
+            Some(threshold) => {
+                require!(
+                    threshold >= self.min_real_sol_threshold
+                        && threshold <= self.max_real_sol_threshold,
+                    ThrustAppError::InvalidThreshold
+                );
+                Ok(threshold)
+            }
+            None => Ok(self.real_sol_threshold),
+        }
+    }
+}
+
+/// Collects the protocol fees swept from pools, graduation fees included, until
+/// `distribute_fees` pays them out to the fee split.
+#[account]
+#[derive(Default)]
+pub struct FeeVault {
+    pub total_distributed: u64,
+}
+
+impl FeeVault {
+    pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
+    pub const PREFIX_SEED: &'static [u8] = b"fee_vault";
 }
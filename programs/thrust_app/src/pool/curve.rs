@@ -0,0 +1,449 @@
+use anchor_lang::prelude::*;
+use crate::{constants::CURVE_PRECISION, error::ThrustAppError};
+
+// ln(2) scaled by CURVE_PRECISION
+const LN_2: u128 = 693_147_180_559_945_309;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub struct PriceStep {
+    pub until_sold: u64, // Step applies until this many tokens are sold
+    pub price: u64,      // Lamports per base unit, scaled by CURVE_PRECISION
+}
+
+/// Price discovery profile of a pool. Apart from `ConstantProduct`, which runs on the
+/// pool's virtual reserves, curves price tokens by the amount already sold and all
+/// prices are lamports per base unit scaled by `CURVE_PRECISION`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub enum CurveType {
+    ConstantProduct,
+    Linear {
+        initial_price: u64,
+        slope: u64, // Price increase per token sold
+    },
+    Exponential {
+        initial_price: u64,
+        growth_rate: u64, // Continuous growth per token sold
+    },
+    Stepped {
+        steps: [Option<PriceStep>; 4], // The last step extends past its `until_sold`
+    },
+}
+
+impl CurveType {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            CurveType::ConstantProduct => true,
+            CurveType::Linear {
+                initial_price,
+                slope,
+            } => *initial_price > 0 || *slope > 0,
+            CurveType::Exponential {
+                initial_price,
+                growth_rate,
+            } => *initial_price > 0 && *growth_rate > 0,
+            CurveType::Stepped { steps } => {
+                let mut last_until = 0;
+                let mut count = 0;
+                for step in steps.iter() {
+                    match step {
+                        Some(step) if step.price > 0 && step.until_sold > last_until => {
+                            last_until = step.until_sold;
+                            count += 1;
+                        }
+                        Some(_) => return false,
+                        None => break,
+                    }
+                }
+                // Steps must be contiguous from the first slot
+                count > 0 && steps.iter().skip(count).all(|step| step.is_none())
+            }
+        }
+    }
+
+    /// Spot price once `sold` tokens are sold, scaled by `CURVE_PRECISION`.
+    pub fn price(&self, sold: u64) -> Result<u128> {
+        let price = match self {
+            CurveType::ConstantProduct => 0,
+            CurveType::Linear {
+                initial_price,
+                slope,
+            } => (*slope as u128)
+                .checked_mul(sold as u128)
+                .and_then(|increase| increase.checked_add(*initial_price as u128))
+                .ok_or(ThrustAppError::MathOverflow)?,
+            CurveType::Exponential {
+                initial_price,
+                growth_rate,
+            } => mul_div(
+                *initial_price as u128,
+                exp_fixed((*growth_rate as u128) * (sold as u128))?,
+                CURVE_PRECISION,
+            )?,
+            CurveType::Stepped { steps } => {
+                let mut price = 0;
+                for step in steps.iter().flatten() {
+                    price = step.price as u128;
+                    if sold < step.until_sold {
+                        break;
+                    }
+                }
+                price
+            }
+        };
+        Ok(price)
+    }
+
+    /// Tokens received for `quote_amount` lamports, rounded down.
+    pub fn base_out(&self, sold: u64, quote_amount: u64) -> Result<u64> {
+        let quote = quote_amount as u128;
+        let base_amount = match self {
+            CurveType::ConstantProduct => 0,
+            CurveType::Linear { slope, .. } => {
+                let price = self.price(sold)?;
+                let budget = quote
+                    .checked_mul(CURVE_PRECISION)
+                    .ok_or(ThrustAppError::MathOverflow)?;
+                if *slope == 0 {
+                    budget / price
+                } else {
+                    // Solve slope * x^2 + 2 * price * x = 2 * budget for x
+                    let slope = *slope as u128;
+                    let discriminant = price
+                        .checked_mul(price)
+                        .and_then(|squared| {
+                            slope
+                                .checked_mul(budget)?
+                                .checked_mul(2)?
+                                .checked_add(squared)
+                        })
+                        .ok_or(ThrustAppError::MathOverflow)?;
+                    (isqrt(discriminant) - price) / slope
+                }
+            }
+            CurveType::Exponential {
+                initial_price,
+                growth_rate,
+            } => {
+                let growth_rate = *growth_rate as u128;
+                let start = exp_fixed(growth_rate * (sold as u128))?;
+                let growth = mul_div(
+                    quote
+                        .checked_mul(growth_rate)
+                        .ok_or(ThrustAppError::MathOverflow)?,
+                    CURVE_PRECISION,
+                    *initial_price as u128,
+                )?;
+                let end = start
+                    .checked_add(growth)
+                    .ok_or(ThrustAppError::MathOverflow)?;
+                let end_sold = ln_fixed(end) / growth_rate;
+                // Absorb fixed point rounding so the pool never undercharges
+                self.affordable_below(sold, end_sold.saturating_sub(sold as u128), quote)?
+            }
+            CurveType::Stepped { steps } => {
+                let mut budget = quote
+                    .checked_mul(CURVE_PRECISION)
+                    .ok_or(ThrustAppError::MathOverflow)?;
+                let mut position = sold as u128;
+                let defined: Vec<&PriceStep> = steps.iter().flatten().collect();
+                for (i, step) in defined.iter().enumerate() {
+                    let price = step.price as u128;
+                    let until = step.until_sold as u128;
+                    let is_last = i + 1 == defined.len();
+                    if position >= until && !is_last {
+                        continue;
+                    }
+                    if !is_last {
+                        let capacity = (until - position)
+                            .checked_mul(price)
+                            .ok_or(ThrustAppError::MathOverflow)?;
+                        if budget >= capacity {
+                            budget -= capacity;
+                            position = until;
+                            continue;
+                        }
+                    }
+                    position += budget / price;
+                    break;
+                }
+                position - sold as u128
+            }
+        };
+        u64::try_from(base_amount).map_err(|_| ThrustAppError::MathOverflow.into())
+    }
+
+    /// Lamports needed to buy `base_amount` tokens, rounded up.
+    pub fn quote_in(&self, sold: u64, base_amount: u64) -> Result<u64> {
+        let quote_amount = self.cost_between(sold, base_amount as u128, true)?;
+        u64::try_from(quote_amount).map_err(|_| ThrustAppError::MathOverflow.into())
+    }
+
+    /// Lamports received for selling `base_amount` tokens, rounded down.
+    pub fn quote_out(&self, sold: u64, base_amount: u64) -> Result<u64> {
+        let from = sold
+            .checked_sub(base_amount)
+            .ok_or(ThrustAppError::InsufficientLiquidity)?;
+        let quote_amount = self.cost_between(from, base_amount as u128, false)?;
+        u64::try_from(quote_amount).map_err(|_| ThrustAppError::MathOverflow.into())
+    }
+
+    // Largest amount up to `estimate` that costs at most `quote`. The estimate is off by
+    // fixed point error only, so galloping down from it and bisecting the last gap keeps
+    // the number of `cost_between` calls logarithmic in that error.
+    fn affordable_below(&self, sold: u64, estimate: u128, quote: u128) -> Result<u128> {
+        let mut high = estimate;
+        if self.cost_between(sold, high, true)? <= quote {
+            return Ok(high);
+        }
+        let mut step = 1;
+        let mut low = loop {
+            let candidate = high.saturating_sub(step);
+            if candidate == 0 || self.cost_between(sold, candidate, true)? <= quote {
+                break candidate;
+            }
+            high = candidate;
+            step = step.saturating_mul(2);
+        };
+        // cost(low) <= quote < cost(high)
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if self.cost_between(sold, mid, true)? <= quote {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(low)
+    }
+
+    // Area under the price curve over `width` tokens, starting at `from` tokens sold
+    fn cost_between(&self, from: u64, width: u128, round_up: bool) -> Result<u128> {
+        let from = from as u128;
+        let to = from
+            .checked_add(width)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        let (numerator, denominator) = match self {
+            CurveType::ConstantProduct => (0, 1),
+            CurveType::Linear {
+                initial_price,
+                slope,
+            } => {
+                // (initial_price * (to - from) + slope * (to^2 - from^2) / 2) * 2
+                let doubled = (*initial_price as u128)
+                    .checked_mul(width)
+                    .and_then(|base| base.checked_mul(2))
+                    .and_then(|base| {
+                        (*slope as u128)
+                            .checked_mul(width)?
+                            .checked_mul(to + from)?
+                            .checked_add(base)
+                    })
+                    .ok_or(ThrustAppError::MathOverflow)?;
+                (doubled, CURVE_PRECISION * 2)
+            }
+            CurveType::Exponential {
+                initial_price,
+                growth_rate,
+            } => {
+                let growth_rate = *growth_rate as u128;
+                let end = exp_fixed(
+                    growth_rate
+                        .checked_mul(to)
+                        .ok_or(ThrustAppError::MathOverflow)?,
+                )?;
+                let start = exp_fixed(growth_rate * from)?;
+                (
+                    (*initial_price as u128)
+                        .checked_mul(end - start)
+                        .ok_or(ThrustAppError::MathOverflow)?,
+                    growth_rate
+                        .checked_mul(CURVE_PRECISION)
+                        .ok_or(ThrustAppError::MathOverflow)?,
+                )
+            }
+            CurveType::Stepped { steps } => {
+                let mut total: u128 = 0;
+                let mut lower = 0;
+                let defined: Vec<&PriceStep> = steps.iter().flatten().collect();
+                for (i, step) in defined.iter().enumerate() {
+                    let upper = if i + 1 == defined.len() {
+                        u128::MAX
+                    } else {
+                        step.until_sold as u128
+                    };
+                    let start = from.max(lower);
+                    let end = to.min(upper);
+                    if end > start {
+                        total = (end - start)
+                            .checked_mul(step.price as u128)
+                            .and_then(|cost| cost.checked_add(total))
+                            .ok_or(ThrustAppError::MathOverflow)?;
+                    }
+                    lower = upper;
+                }
+                (total, CURVE_PRECISION)
+            }
+        };
+        div_round(numerator, denominator, round_up)
+    }
+}
+
+fn div_round(numerator: u128, denominator: u128, round_up: bool) -> Result<u128> {
+    let quotient = if round_up {
+        numerator
+            .checked_add(denominator - 1)
+            .ok_or(ThrustAppError::MathOverflow)?
+            / denominator
+    } else {
+        numerator / denominator
+    };
+    Ok(quotient)
+}
+
+// a * b / c without overflowing on the intermediate product when a * b does not fit
+fn mul_div(a: u128, b: u128, c: u128) -> Result<u128> {
+    let result = match a.checked_mul(b) {
+        Some(product) => Some(product / c),
+        None => (a / c)
+            .checked_mul(b)
+            .and_then(|high| high.checked_add((a % c).checked_mul(b)? / c)),
+    };
+    result.ok_or(ThrustAppError::MathOverflow.into())
+}
+
+pub(crate) fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = 1u128 << ((128 - value.leading_zeros()) / 2 + 1);
+    loop {
+        let y = (x + value / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+// e^(x / CURVE_PRECISION), scaled by CURVE_PRECISION
+fn exp_fixed(x: u128) -> Result<u128> {
+    let n = x / LN_2;
+    let r = x % LN_2;
+
+    // Taylor series of e^r for r in [0, ln 2)
+    let mut term = CURVE_PRECISION;
+    let mut sum = CURVE_PRECISION;
+    let mut i = 1;
+    while term > 0 {
+        term = term * r / CURVE_PRECISION / i;
+        sum += term;
+        i += 1;
+    }
+
+    require!(
+        (sum.leading_zeros() as u128) > n,
+        ThrustAppError::MathOverflow
+    );
+    Ok(sum << n)
+}
+
+// ln(y / CURVE_PRECISION) for y >= CURVE_PRECISION, scaled by CURVE_PRECISION
+fn ln_fixed(y: u128) -> u128 {
+    // Range reduce y to z * 2^n with z in [1, 2)
+    let mut n = 0;
+    let mut z = y;
+    while z >= 2 * CURVE_PRECISION {
+        z /= 2;
+        n += 1;
+    }
+
+    // ln(z) = 2 * atanh((z - 1) / (z + 1))
+    let t = (z - CURVE_PRECISION) * CURVE_PRECISION / (z + CURVE_PRECISION);
+    let t_squared = t * t / CURVE_PRECISION;
+    let mut power = t;
+    let mut sum = 0;
+    let mut k = 1;
+    while power > 0 {
+        sum += power / k;
+        power = power * t_squared / CURVE_PRECISION;
+        k += 2;
+    }
+
+    n * LN_2 + 2 * sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOLD: [u64; 4] = [0, 1_000_000, 150_000_000_000_000, 700_000_000_000_000];
+    const QUOTE: [u64; 4] = [1, 1_000_000, 1_000_000_000, 50_000_000_000];
+
+    fn curves() -> [CurveType; 3] {
+        [
+            CurveType::Linear {
+                initial_price: 28_000_000_000_000,
+                slope: 3,
+            },
+            CurveType::Exponential {
+                initial_price: 28_000_000_000_000,
+                growth_rate: 5_000,
+            },
+            CurveType::Stepped {
+                steps: [
+                    Some(PriceStep {
+                        until_sold: 200_000_000_000_000,
+                        price: 20_000_000_000_000,
+                    }),
+                    Some(PriceStep {
+                        until_sold: 500_000_000_000_000,
+                        price: 50_000_000_000_000,
+                    }),
+                    Some(PriceStep {
+                        until_sold: 800_000_000_000_000,
+                        price: 100_000_000_000_000,
+                    }),
+                    None,
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn base_out_never_costs_more_than_the_quote() {
+        for curve in curves() {
+            for sold in SOLD {
+                for quote_amount in QUOTE {
+                    let base_amount = curve.base_out(sold, quote_amount).unwrap();
+                    assert!(
+                        curve.quote_in(sold, base_amount).unwrap() <= quote_amount,
+                        "{curve:?} sold {sold} quote {quote_amount}"
+                    );
+                    assert!(
+                        curve.quote_in(sold, base_amount + 1).unwrap() > quote_amount,
+                        "{curve:?} sold {sold} quote {quote_amount} leaves tokens unbought"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn quote_in_buys_back_at_least_the_tokens_priced() {
+        for curve in curves() {
+            for sold in SOLD {
+                for base_amount in [1, 1_000, 1_000_000_000, 10_000_000_000_000] {
+                    let quote_amount = curve.quote_in(sold, base_amount).unwrap();
+                    assert!(
+                        curve.base_out(sold, quote_amount).unwrap() >= base_amount,
+                        "{curve:?} sold {sold} base {base_amount}"
+                    );
+                    assert!(
+                        curve.quote_out(sold + base_amount, base_amount).unwrap() <= quote_amount,
+                        "{curve:?} sold {sold} base {base_amount} sells back for more"
+                    );
+                }
+            }
+        }
+    }
+}
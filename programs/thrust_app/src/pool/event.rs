@@ -13,8 +13,14 @@ pub struct CreateEvent {
 pub struct TradeEvent {
     pub user: Pubkey,
     pub mint: Pubkey,
-    pub sol_amount: u64,
+    pub sol_amount: u64,          // Filled SOL amount, fee included on buys and deducted on sells
     pub token_amount: u64,
+    pub fee: u64,
+    pub creator_fee: u64,         // Part of the fee paid to the creator vault
+    pub fee_tier: Option<u8>,     // Volume tier that lowered the trading fee, if any
+    pub integrator: Option<Pubkey>, // Frontend or bot the trade was routed through
+    pub integrator_fee: u64,      // Charged on top of the fee and paid to the integrator
+    pub unfilled_sol_amount: u64, // Part of a buy not filled because the curve completed
     pub base_reserves: u64,
     pub quote_reserves: u64,
     pub is_buy: bool,
@@ -27,3 +33,68 @@ pub struct CompleteEvent {
     pub mint: Pubkey,
     pub timestamp: i64,
 }
+
+#[event]
+pub struct MigrateEvent {
+    pub mint: Pubkey,
+    pub amm_pool: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub lp_burned: u64,
+    pub keeper: Pubkey,
+    pub bounty: u64, // Paid to the keeper out of the graduation fee
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwapEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount_in: u64, // SOL on buys, tokens on sells
+    pub amount_out: u64,
+    pub fee: u64,       // Part of `amount_in` left in the AMM
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    pub is_buy: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub lp_amount: u64,
+    pub is_add: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimCreatorFeesEvent {
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimReferralRewardsEvent {
+    pub referrer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SweepProtocolFeesEvent {
+    pub mint: Pubkey,
+    pub amount: u64, // Moved from the pool's reserve to the fee vault
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DistributeFeesEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
@@ -1,27 +1,28 @@
 use anchor_lang::{prelude::*, system_program};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    associated_token::{self, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 use crate::{
-    constants::{FEE_PER_DIV, GRADUATE_FEE, REAL_SOL_THRESHOLD, RESERVE_SEED},
+    constants::{CREATOR_VAULT_SEED, RESERVE_SEED},
     error::ThrustAppError,
     main_state,
-    utils::{calculate_trading_fee, verify_signed_message},
-    ClosureCondition, CompleteEvent, MainState, PoolState, TradeEvent, UserState, WaitingRoomState,
+    utils::{calculate_trading_fee, calculate_volume_usd, transfer_tokens, verify_signed_message},
+    pay_referral_rewards, BuyFill, ClosureCondition, CompleteEvent, FeeOverride, MainState,
+    PoolState, ReferralCode, ReferrerState, TradeEvent, UserState, WaitingRoomState,
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct BuyInput {
     pub amount: u64,         // Amount of SOL to spend
+    pub min_tokens_out: u64, // Minimum amount of tokens to receive
     pub signature: [u8; 65], // ECDSA signature of the empty message
+    pub integrator_fee: u64, // Fee rate paid to the integrator account, 0 for none
 }
 
-pub fn buy(ctx: Context<ABuy>, input: BuyInput) -> Result<()> {
-    let main_state = &mut ctx.accounts.main_state;
-    let pool_state = &mut ctx.accounts.pool_state;
-    let reserve_pda = &mut ctx.accounts.reserve_pda;
-    let user_state = &mut ctx.accounts.user_state;
+pub fn buy<'info>(ctx: Context<'_, '_, '_, 'info, ABuy<'info>>, input: BuyInput) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    let pool_state = &ctx.accounts.pool_state;
     let current_timestamp = Clock::get()?.unix_timestamp as u64;
     let amount = input.amount;
 
@@ -37,206 +38,395 @@ pub fn buy(ctx: Context<ABuy>, input: BuyInput) -> Result<()> {
         pool_state.complete.eq(&false),
         ThrustAppError::BondingCurveComplete
     );
+    ctx.accounts.check_integrator_fee(input.integrator_fee)?;
 
-    let start_trade_timestamp = pool_state.start_trade_timestamp;
+    let (trading_fee, _) = ctx.accounts.pool_state.user_trading_fee(
+        main_state,
+        &ctx.accounts.user_state,
+        ctx.accounts.fee_override.as_deref().map(|fee_override| &**fee_override),
+    );
+    let fill = ctx
+        .accounts
+        .pool_state
+        .apply_buy(amount, trading_fee, input.integrator_fee)?;
+    require!(
+        fill.output_amount >= input.min_tokens_out,
+        ThrustAppError::SlippageExceeded
+    );
 
-    // Check Waiting Room state
-    match &mut pool_state.waiting_room_state {
-        WaitingRoomState::Disabled => {
-            // No restrictions, proceed with normal buy
-        }
-        WaitingRoomState::Enabled {
-            closed,
-            wallet_limit_percent,
-            total_buy_volume,
-            participants,
-            min_trades,
-            max_participants,
-            closure_condition,
-        } => {
-            // Check closure conditions if not closed
-            if !*closed {
-                match closure_condition {
-                    ClosureCondition::TimeBased { close_timestamp } => {
-                        if current_timestamp > *close_timestamp {
-                            *closed = true;
+    // Waiting room limits apply to what was actually filled
+    ctx.accounts
+        .check_waiting_room(&fill, &input.signature, current_timestamp)?;
+
+    ctx.accounts.bind_referrer()?;
+    ctx.accounts
+        .settle_buy(&ctx.bumps, &fill, ctx.remaining_accounts)
+}
+
+impl<'info> ABuy<'info> {
+    /// Integrator fees are bounded by the main state and need an account to be paid to.
+    pub fn check_integrator_fee(&self, integrator_fee: u64) -> Result<()> {
+        self.main_state.validate_integrator_fee(integrator_fee)?;
+        require!(
+            integrator_fee == 0 || self.integrator.is_some(),
+            ThrustAppError::IntegratorRequired
+        );
+        Ok(())
+    }
+
+    pub fn check_waiting_room(
+        &mut self,
+        fill: &BuyFill,
+        signature: &[u8; 65],
+        current_timestamp: u64,
+    ) -> Result<()> {
+        let main_state = &self.main_state;
+        let user_state = &self.user_state;
+        let total_supply = self.pool_state.total_supply;
+
+        // Check Waiting Room state
+        match &mut self.pool_state.waiting_room_state {
+            WaitingRoomState::Disabled => {
+                // No restrictions, proceed with normal buy
+            }
+            WaitingRoomState::Enabled {
+                closed,
+                wallet_limit_percent,
+                total_buy_volume,
+                participants,
+                min_trades,
+                max_participants,
+                closure_condition,
+            } => {
+                // Check closure conditions if not closed
+                if !*closed {
+                    match closure_condition {
+                        ClosureCondition::TimeBased { close_timestamp } => {
+                            if current_timestamp > *close_timestamp {
+                                *closed = true;
+                            }
                         }
-                    }
-                    ClosureCondition::ParticipantCount { max_participants } => {
-                        if *participants >= *max_participants {
-                            *closed = true;
+                        ClosureCondition::ParticipantCount { max_participants } => {
+                            if *participants >= *max_participants {
+                                *closed = true;
+                            }
                         }
-                    }
-                    ClosureCondition::BuyVolume { close_volume } => {
-                        if *total_buy_volume >= *close_volume {
-                            *closed = true;
+                        ClosureCondition::BuyVolume { close_volume } => {
+                            if *total_buy_volume >= *close_volume {
+                                *closed = true;
+                            }
                         }
                     }
                 }
-            }
-
-            // If Waiting Room is closed, verify the caller's signature
-            if *closed {
-                verify_signed_message(&input.signature, &main_state.verify_signer_pubkey);
-            }
 
-            // Check user qualification (only if Waiting Room is enabled)
-            require!(
-                user_state.trade_count >= (*min_trades).into(),
-                ThrustAppError::InsufficientTrades
-            );
+                // If Waiting Room is closed, verify the caller's signature
+                if *closed {
+                    require!(
+                        verify_signed_message(signature, &main_state.verify_signer_pubkey),
+                        ThrustAppError::InvalidSignature
+                    );
+                }
 
-            // Check wallet limit
-            let max_allowed = (main_state.total_token_supply * *wallet_limit_percent as u64) / 100;
-            let user_balance = ctx.accounts.buyer_base_ata.amount;
-            require!(
-                user_balance + amount <= max_allowed,
-                ThrustAppError::ExceedsWalletLimit
-            );
+                // Check user qualification (only if Waiting Room is enabled)
+                require!(
+                    user_state.trade_count >= (*min_trades).into(),
+                    ThrustAppError::InsufficientTrades
+                );
+
+                // Check wallet limit
+                let max_allowed = (total_supply as u128)
+                    * (*wallet_limit_percent as u128)
+                    / 100;
+                let user_balance = self.buyer_base_ata.amount;
+                require!(
+                    (user_balance as u128) + (fill.output_amount as u128) <= max_allowed,
+                    ThrustAppError::ExceedsWalletLimit
+                );
 
-            // Update waiting room state
-            if user_balance == 0 {
-                *participants += 1;
-            }
-            *total_buy_volume += amount;
-
-            // Auto-close if condition met
-            if !*closed {
-                match closure_condition {
-                    ClosureCondition::ParticipantCount { max_participants } if *participants >= *max_participants => {
-                        *closed = true;
-                    }
-                    ClosureCondition::BuyVolume { close_volume } if *total_buy_volume >= *close_volume => {
-                        *closed = true;
-                    }
-                    _ => {}
-                }
             }
         }
-    }
 
-    let mut fee = calculate_trading_fee(main_state.trading_fee, amount);
-    let mut input_amount = amount - fee;
-    if (input_amount + pool_state.real_quote_reserves > REAL_SOL_THRESHOLD) {
-        input_amount = REAL_SOL_THRESHOLD - pool_state.real_quote_reserves;
-        fee = calculate_trading_fee(main_state.trading_fee, input_amount);
+        // Update waiting room state
+        let new_participant = self.buyer_base_ata.amount == 0;
+        self.pool_state
+            .waiting_room_state
+            .record_buy(new_participant, fill.sol_amount()?)
     }
-    let output_amount = pool_state.compute_receivable_amount_on_buy(input_amount);
-    let mut referral_reward = 0;
-
-    const STALENESS_THRESHOLD: u64 = 60; // staleness threshold in seconds
-    let sol_price = main_state.sol_price;
-
-    let trading_volume_usd = input_amount * sol_price / 1_000_000_000;
-    user_state.trade_count += 1;
-    user_state.trading_volume_sol += input_amount;
-    user_state.trading_volume_usd += trading_volume_usd;
 
-    msg!("Trading volume in USD: {}", trading_volume_usd);
+    /// Binds the passed referrer on the buyer's first referred trade, see `ReferrerState::bind`.
+    pub fn bind_referrer(&mut self) -> Result<()> {
+        if let Some(referrer_state) = self.referrer_state.as_deref_mut() {
+            referrer_state.bind(
+                &mut self.user_state,
+                &self.buyer.key(),
+                self.referrer_user_state.as_deref(),
+            )?;
+        }
+        Ok(())
+    }
 
-    // Store referrer to user state, only 1 time store.
-    if user_state.referrer == Pubkey::default() && ctx.accounts.referrer.key() != Pubkey::default()
-    {
-        user_state.referrer = ctx.accounts.referrer.key();
+    /// Moves funds for a buy already applied to the curve, see `BuyTransfers::settle`.
+    /// `remaining_accounts` carry the transfer hook accounts of hooked Token-2022 mints.
+    pub fn settle_buy(
+        &mut self,
+        bumps: &ABuyBumps,
+        fill: &BuyFill,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let transfers = BuyTransfers {
+            buyer: self.buyer.to_account_info(),
+            creator_vault: self.creator_vault.to_account_info(),
+            integrator: self
+                .integrator
+                .as_ref()
+                .map(|integrator| integrator.to_account_info()),
+            mint: self.mint.to_account_info(),
+            decimals: self.mint.decimals,
+            buyer_base_ata: self.buyer_base_ata.to_account_info(),
+            reserve_pda: self.reserve_pda.to_account_info(),
+            reserver_base_ata: self.reserver_base_ata.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            remaining_accounts: remaining_accounts.to_vec(),
+            pool_bump: bumps.pool_state,
+            fee_override: self
+                .fee_override
+                .as_deref()
+                .map(|fee_override| (**fee_override).clone()),
+        };
+        transfers.settle(
+            &self.main_state,
+            &mut self.pool_state,
+            &mut self.user_state,
+            [
+                self.referrer_state.as_deref_mut(),
+                self.level2_referrer_state.as_deref_mut(),
+                self.level3_referrer_state.as_deref_mut(),
+            ],
+            fill,
+        )
     }
+}
 
-    if user_state.referrer != Pubkey::default()
-        && user_state.referrer == ctx.accounts.referrer.key()
-        && user_state.refer_trade_num <= main_state.referral_trade_limit
-    {
-        referral_reward = calculate_trading_fee(main_state.referral_reward_fee, fee);
+/// Accounts moving the funds of a buy, shared by `buy` and the creator's initial buy.
+pub struct BuyTransfers<'info> {
+    pub buyer: AccountInfo<'info>,
+    pub creator_vault: AccountInfo<'info>,
+    pub integrator: Option<AccountInfo<'info>>,
+    pub mint: AccountInfo<'info>,
+    pub decimals: u8,
+    pub buyer_base_ata: AccountInfo<'info>,
+    pub reserve_pda: AccountInfo<'info>,
+    pub reserver_base_ata: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub remaining_accounts: Vec<AccountInfo<'info>>, // Transfer hook accounts
+    pub pool_bump: u8,
+    pub fee_override: Option<FeeOverride>, // Buyer's negotiated fee, if any
+}
 
-        let referral_transfer_ctx = CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            system_program::Transfer {
-                from: ctx.accounts.buyer.to_account_info(),
-                to: ctx.accounts.referrer.to_account_info(),
+impl<'info> BuyTransfers<'info> {
+    /// Creates the buyer's associated token account when it doesn't exist yet.
+    pub fn create_buyer_ata(&self, associated_token_program: &AccountInfo<'info>) -> Result<()> {
+        associated_token::create_idempotent(CpiContext::new(
+            associated_token_program.clone(),
+            associated_token::Create {
+                payer: self.buyer.clone(),
+                associated_token: self.buyer_base_ata.clone(),
+                authority: self.buyer.clone(),
+                mint: self.mint.clone(),
+                system_program: self.system_program.clone(),
+                token_program: self.token_program.clone(),
             },
+        ))
+    }
+
+    /// Creator's first buy, made inside pool creation so nobody can trade before it.
+    /// Same curve and fees as `buy`; the waiting room wallet limit still applies.
+    pub fn initial_buy(
+        self,
+        main_state: &MainState,
+        pool_state: &mut Account<'info, PoolState>,
+        user_state: &mut UserState,
+        referral_chain: [Option<&mut Account<'info, ReferrerState>>; ReferrerState::LEVELS],
+        sol_amount: u64,
+        min_tokens_out: u64,
+    ) -> Result<()> {
+        let (trading_fee, _) =
+            pool_state.user_trading_fee(main_state, user_state, self.fee_override.as_ref());
+        let fill = pool_state.apply_buy(sol_amount, trading_fee, 0)?;
+        require!(
+            fill.output_amount >= min_tokens_out,
+            ThrustAppError::SlippageExceeded
         );
-        system_program::transfer(referral_transfer_ctx, referral_reward)?;
-        user_state.refer_trade_num += 1;
+        if let WaitingRoomState::Enabled {
+            wallet_limit_percent,
+            ..
+        } = pool_state.waiting_room_state
+        {
+            let max_allowed =
+                (pool_state.total_supply as u128) * (wallet_limit_percent as u128) / 100;
+            require!(
+                (fill.output_amount as u128) <= max_allowed,
+                ThrustAppError::ExceedsWalletLimit
+            );
+        }
+        // The creator is the room's first participant
+        pool_state
+            .waiting_room_state
+            .record_buy(true, fill.sol_amount()?)?;
+        self.settle(main_state, pool_state, user_state, referral_chain, &fill)
     }
 
-    // Transfer Fee in SOL from buyer to fee address
-    let fee_transfer_ctx = CpiContext::new(
-        ctx.accounts.system_program.to_account_info(),
-        system_program::Transfer {
-            from: ctx.accounts.buyer.to_account_info(),
-            to: ctx.accounts.fee_recipient.to_account_info(),
-        },
-    );
-    system_program::transfer(fee_transfer_ctx, fee - referral_reward)?;
-
-    // Transfer SOL from buyer to Pool
-    let input_amount_transfer_ctx = CpiContext::new(
-        ctx.accounts.system_program.to_account_info(),
-        system_program::Transfer {
-            from: ctx.accounts.buyer.to_account_info(),
-            to: reserve_pda.to_account_info(),
-        },
-    );
-    system_program::transfer(input_amount_transfer_ctx, input_amount)?;
-
-    // Transfer Tokens to Buyer(User) from reserve ata(Pool)
-    let token_transfer_cpi_account = Transfer {
-        from: ctx.accounts.reserver_base_ata.to_account_info(),
-        to: ctx.accounts.buyer_base_ata.to_account_info(),
-        authority: pool_state.to_account_info(),
-    };
-
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            token_transfer_cpi_account,
-            &[&[
-                PoolState::PREFIX_SEED,
-                pool_state.mint.as_ref(),
-                &[ctx.bumps.pool_state],
-            ]],
-        ),
-        output_amount,
-    )?;
-
-    emit!(TradeEvent {
-        user: ctx.accounts.buyer.to_account_info().key(),
-        mint: pool_state.mint,
-        token_amount: output_amount,
-        sol_amount: amount,
-        base_reserves: pool_state.real_base_reserves + pool_state.virt_base_reserves,
-        quote_reserves: pool_state.virt_quote_reserves + pool_state.real_quote_reserves,
-        is_buy: true,
-        timestamp: Clock::get()?.unix_timestamp,
-    });
-
-    if (pool_state.real_quote_reserves >= REAL_SOL_THRESHOLD) {
-        pool_state.complete = true;
-
-        let pool_signer_seeds: &[&[u8]] = &[
-            RESERVE_SEED,
-            pool_state.mint.as_ref(),
-            &[ctx.bumps.reserve_pda],
-        ];
-        let pool_signer: &[&[&[u8]]] = &[pool_signer_seeds];
-
-        // Transfer 5 SOL from pool to fee
-        let graduate_solfee_transfer_ctx = CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
+    /// The filled SOL goes to the reserve along with the protocol fee, the referral rewards
+    /// and the creator fee are paid into their vaults and the bought tokens go to the buyer.
+    /// Nothing is charged for the unfilled part.
+    pub fn settle(
+        self,
+        main_state: &MainState,
+        pool_state: &mut Account<'info, PoolState>,
+        user_state: &mut UserState,
+        referral_chain: [Option<&mut Account<'info, ReferrerState>>; ReferrerState::LEVELS],
+        fill: &BuyFill,
+    ) -> Result<()> {
+        let BuyFill {
+            input_amount,
+            fee,
+            integrator_fee,
+            output_amount,
+            unfilled_amount,
+        } = *fill;
+        let sol_price = main_state.sol_price;
+        // Tier the fee was charged at, before this trade adds to the volume
+        let (_, fee_tier) =
+            pool_state.user_trading_fee(main_state, user_state, self.fee_override.as_ref());
+
+        let trading_volume_usd = calculate_volume_usd(input_amount, sol_price)?;
+        user_state.record_trade(input_amount, trading_volume_usd)?;
+
+        msg!("Trading volume in USD: {}", trading_volume_usd);
+
+        let referral_reward = pay_referral_rewards(
+            main_state,
+            user_state,
+            referral_chain,
+            &self.buyer,
+            &[],
+            &self.system_program,
+            input_amount,
+            fee,
+        )?;
+
+        let creator_fee = pool_state.accrue_creator_fee(fee)?;
+        if creator_fee > 0 {
+            let creator_fee_transfer_ctx = CpiContext::new(
+                self.system_program.clone(),
+                system_program::Transfer {
+                    from: self.buyer.clone(),
+                    to: self.creator_vault.clone(),
+                },
+            );
+            system_program::transfer(creator_fee_transfer_ctx, creator_fee)?;
+        }
+
+        if let Some(integrator) = self.integrator.as_ref().filter(|_| integrator_fee > 0) {
+            let integrator_fee_transfer_ctx = CpiContext::new(
+                self.system_program.clone(),
+                system_program::Transfer {
+                    from: self.buyer.clone(),
+                    to: integrator.clone(),
+                },
+            );
+            system_program::transfer(integrator_fee_transfer_ctx, integrator_fee)?;
+        }
+
+        // The protocol fee travels with the SOL into the reserve, see `sweep_protocol_fees`
+        let protocol_fee = fee
+            .checked_sub(referral_reward)
+            .and_then(|fee| fee.checked_sub(creator_fee))
+            .ok_or(ThrustAppError::MathOverflow)?;
+        pool_state.accrue_protocol_fee(protocol_fee)?;
+
+        // Transfer SOL from buyer to Pool
+        let input_amount_transfer_ctx = CpiContext::new(
+            self.system_program.clone(),
             system_program::Transfer {
-                from: reserve_pda.to_account_info(),
-                to: ctx.accounts.fee_recipient.to_account_info(),
+                from: self.buyer.clone(),
+                to: self.reserve_pda.clone(),
             },
-            pool_signer,
         );
-        system_program::transfer(graduate_solfee_transfer_ctx, GRADUATE_FEE)?;
-
-        emit!(CompleteEvent {
-            user: ctx.accounts.buyer.to_account_info().key(),
+        system_program::transfer(
+            input_amount_transfer_ctx,
+            input_amount
+                .checked_add(protocol_fee)
+                .ok_or(ThrustAppError::MathOverflow)?,
+        )?;
+
+        // Transfer Tokens to Buyer(User) from reserve ata(Pool)
+        let token_transfer_cpi_account = TransferChecked {
+            from: self.reserver_base_ata.clone(),
+            mint: self.mint.clone(),
+            to: self.buyer_base_ata.clone(),
+            authority: pool_state.to_account_info(),
+        };
+
+        transfer_tokens(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                token_transfer_cpi_account,
+                &[&[
+                    PoolState::PREFIX_SEED,
+                    pool_state.mint.as_ref(),
+                    &[self.pool_bump],
+                ]],
+            )
+            .with_remaining_accounts(self.remaining_accounts),
+            output_amount,
+            self.decimals,
+        )?;
+
+        emit!(TradeEvent {
+            user: self.buyer.key(),
             mint: pool_state.mint,
+            token_amount: output_amount,
+            sol_amount: fill.sol_amount()?,
+            fee,
+            creator_fee,
+            fee_tier,
+            integrator: self.integrator.as_ref().map(|integrator| integrator.key()),
+            integrator_fee,
+            unfilled_sol_amount: unfilled_amount,
+            base_reserves: pool_state.base_reserves()?,
+            quote_reserves: pool_state.quote_reserves()?,
+            is_buy: true,
             timestamp: Clock::get()?.unix_timestamp,
         });
-    }
 
-    Ok(())
+        if pool_state.is_graduated(main_state)? {
+            pool_state.complete = true;
+
+            // The graduation fee is swept along with the trading fees, less the bounty
+            // of the keeper migrating the pool when it doesn't trade on the built-in AMM
+            let graduate_fee = pool_state.graduate_fee;
+            if main_state.built_in_amm {
+                pool_state.accrue_protocol_fee(graduate_fee)?;
+                pool_state.open_amm(main_state.amm_fee)?;
+            } else {
+                let bounty =
+                    calculate_trading_fee(main_state.graduation_bounty_share, graduate_fee)?;
+                pool_state.graduation_bounty = bounty;
+                pool_state.accrue_protocol_fee(graduate_fee - bounty)?;
+            }
+
+            emit!(CompleteEvent {
+                user: self.buyer.key(),
+                mint: pool_state.mint,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -244,16 +434,11 @@ pub struct ABuy<'info> {
     #[account(mut)]
     pub buyer: Signer<'info>,
     #[account(
-        mut,
         seeds = [MainState::PREFIX_SEED],
         bump,
     )]
     pub main_state: Box<Account<'info, MainState>>,
 
-    /// CHECK: This address is fee recipient address
-    #[account(mut, address = main_state.fee_recipient,)]
-    pub fee_recipient: AccountInfo<'info>,
-
     #[account(
         init_if_needed,
         payer = buyer,
@@ -266,8 +451,57 @@ pub struct ABuy<'info> {
     )]
     pub user_state: Box<Account<'info, UserState>>,
 
-    /// CHECK: Ensure referrer is valid address
-    pub referrer: AccountInfo<'info>,
+    /// Referrer's reward vault, for trades made through a referral
+    #[account(
+        mut,
+        seeds = [
+            ReferrerState::PREFIX_SEED,
+            referral_code
+                .as_ref()
+                .map_or(referrer_state.referrer, |referral_code| referral_code.referrer)
+                .as_ref(),
+        ],
+        bump,
+        constraint = referrer_user_state.is_some() || user_state.referrer != Pubkey::default()
+            @ ThrustAppError::ReferrerUserStateRequired,
+    )]
+    pub referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Reward vault of the referrer's referrer, for multi-level referral rewards
+    #[account(
+        mut,
+        seeds = [ReferrerState::PREFIX_SEED, level2_referrer_state.referrer.as_ref()],
+        bump,
+    )]
+    pub level2_referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Reward vault of the third referral level
+    #[account(
+        mut,
+        seeds = [ReferrerState::PREFIX_SEED, level3_referrer_state.referrer.as_ref()],
+        bump,
+    )]
+    pub level3_referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Referrer's own user state, needed when the referrer gets bound to the buyer
+    pub referrer_user_state: Option<Box<Account<'info, UserState>>>,
+
+    /// Referral code the trade came through, `referrer_state` is derived from its referrer
+    #[account(
+        constraint = referrer_state.is_some() @ ThrustAppError::ReferrerStateRequired,
+    )]
+    pub referral_code: Option<Box<Account<'info, ReferralCode>>>,
+
+    /// Buyer's negotiated trading fee, for wallets that have one
+    #[account(
+        seeds = [FeeOverride::PREFIX_SEED, buyer.key().as_ref()],
+        bump,
+    )]
+    pub fee_override: Option<Box<Account<'info, FeeOverride>>>,
+
+    /// CHECK: Frontend or bot receiving the integrator fee, any address
+    #[account(mut)]
+    pub integrator: Option<UncheckedAccount<'info>>,
 
     #[account(
         mut,
@@ -280,15 +514,16 @@ pub struct ABuy<'info> {
     pub pool_state: Box<Account<'info, PoolState>>,
 
     #[account(address = pool_state.mint)]
-    pub mint: Box<Account<'info, Mint>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         init_if_needed,
         payer = buyer,
         associated_token::mint = mint,
+        associated_token::token_program = token_program,
         associated_token::authority = buyer,
     )]
-    pub buyer_base_ata: Box<Account<'info, TokenAccount>>,
+    pub buyer_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -299,13 +534,25 @@ pub struct ABuy<'info> {
         bump,
     )]
     pub reserve_pda: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            CREATOR_VAULT_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub creator_vault: SystemAccount<'info>,
+
     #[account(
         mut,
         associated_token::mint = mint,
+        associated_token::token_program = token_program,
         associated_token::authority = pool_state,
     )]
-    pub reserver_base_ata: Box<Account<'info, TokenAccount>>,
+    pub reserver_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
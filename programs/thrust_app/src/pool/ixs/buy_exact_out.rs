@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::{error::ThrustAppError, utils::calculate_trading_fee, ABuy, BuyFill};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BuyExactOutInput {
+    pub amount: u64,         // Amount of tokens to receive
+    pub max_sol_in: u64,     // Maximum amount of SOL to spend, fee included
+    pub signature: [u8; 65], // ECDSA signature of the empty message
+    pub integrator_fee: u64, // Fee rate paid to the integrator account, 0 for none
+}
+pub fn buy_exact_out<'info>(
+    ctx: Context<'_, '_, '_, 'info, ABuy<'info>>,
+    input: BuyExactOutInput,
+) -> Result<()> {
+    ctx.accounts.check_integrator_fee(input.integrator_fee)?;
+    let main_state = &ctx.accounts.main_state;
+    let pool_state = &mut ctx.accounts.pool_state;
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    let output_amount = input.amount;
+
+    require!(
+        main_state.initialized.eq(&true),
+        ThrustAppError::Uninitialized
+    );
+    require!(
+        current_timestamp >= pool_state.start_trade_timestamp,
+        ThrustAppError::TradeStartTimeNotReached
+    );
+    require!(
+        pool_state.complete.eq(&false),
+        ThrustAppError::BondingCurveComplete
+    );
+
+    // Price the exact token amount on the curve, then charge the fee on top
+    let input_amount = pool_state.compute_payable_amount_on_buy(output_amount)?;
+    require!(
+        pool_state.real_quote_reserves <= pool_state.real_sol_threshold,
+        ThrustAppError::ThresholdExceeded
+    );
+    let fill = BuyFill {
+        input_amount,
+        fee: calculate_trading_fee(
+            pool_state
+                .user_trading_fee(
+                    main_state,
+                    &ctx.accounts.user_state,
+                    ctx.accounts.fee_override.as_deref().map(|fee_override| &**fee_override),
+                )
+                .0,
+            input_amount,
+        )?,
+        integrator_fee: calculate_trading_fee(input.integrator_fee, input_amount)?,
+        output_amount,
+        unfilled_amount: 0,
+    };
+    require!(
+        fill.sol_amount()? <= input.max_sol_in,
+        ThrustAppError::SlippageExceeded
+    );
+
+    ctx.accounts
+        .check_waiting_room(&fill, &input.signature, current_timestamp)?;
+
+    ctx.accounts.bind_referrer()?;
+    ctx.accounts
+        .settle_buy(&ctx.bumps, &fill, ctx.remaining_accounts)
+}
@@ -0,0 +1,67 @@
+use anchor_lang::{prelude::*, system_program};
+use crate::{
+    constants::CREATOR_VAULT_SEED, error::ThrustAppError, ClaimCreatorFeesEvent, PoolState,
+};
+
+/// Sends the creator fees accrued in the pool's vault to the pool creator. The vault keeps
+/// its rent exempt minimum.
+pub fn claim_creator_fees(ctx: Context<AClaimCreatorFees>) -> Result<()> {
+    let pool_state = &ctx.accounts.pool_state;
+    let creator_vault = &ctx.accounts.creator_vault;
+
+    let rent_exempt = Rent::get()?.minimum_balance(0);
+    let amount = creator_vault.lamports().saturating_sub(rent_exempt);
+    require!(amount > 0, ThrustAppError::NothingToClaim);
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: creator_vault.to_account_info(),
+                to: ctx.accounts.owner.to_account_info(),
+            },
+            &[&[
+                CREATOR_VAULT_SEED,
+                pool_state.mint.as_ref(),
+                &[ctx.bumps.creator_vault],
+            ]],
+        ),
+        amount,
+    )?;
+
+    emit!(ClaimCreatorFeesEvent {
+        creator: ctx.accounts.owner.key(),
+        mint: pool_state.mint,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AClaimCreatorFees<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [
+            PoolState::PREFIX_SEED,
+            pool_state.mint.as_ref(),
+        ],
+        bump,
+        has_one = owner @ ThrustAppError::Unauthorised,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        mut,
+        seeds = [
+            CREATOR_VAULT_SEED,
+            pool_state.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub creator_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
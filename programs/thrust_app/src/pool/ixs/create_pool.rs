@@ -1,4 +1,4 @@
-use anchor_lang::{prelude::*, solana_program::program::invoke_signed};
+use anchor_lang::{prelude::*, solana_program::program::invoke_signed, system_program};
 use anchor_spl::{
     associated_token::AssociatedToken,
     token::{self, Mint, MintTo, SetAuthority, SyncNative, Token, TokenAccount, Transfer},
@@ -8,8 +8,12 @@ use mpl_token_metadata::types::DataV2;
 use mpl_token_metadata::ID as METADATA_PROGRAM_ID;
 
 use crate::{
-    constants::RESERVE_SEED, constants::TOTAL_SUPPLY, error::ThrustAppError, CreateEvent,
-    MainState, PoolState, TaxType, UserState, WaitingRoomConfig, WaitingRoomState,
+    constants::{CREATOR_VAULT_SEED, RESERVE_SEED},
+    error::ThrustAppError,
+    utils::calculate_sol_from_usd,
+    BuyTransfers, CreateEvent, CurveType, FeeOverride, GraduationMode, LaunchProfile, MainState,
+    PoolState, ReferralCode, ReferrerState, TaxType, UserState, WaitingRoomConfig,
+    WaitingRoomState,
 };
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug)]
@@ -20,14 +24,31 @@ pub struct CreatePoolInput {
     pub trade_start_time: u64,
     pub tax_type: TaxType,
     pub waiting_room_config: Option<WaitingRoomConfig>,
+    pub curve_type: CurveType,             // Ignored when a launch profile is used
+    pub real_sol_threshold: Option<u64>,   // Defaults to the main state one, ignored with a profile
+    pub graduation_mode: GraduationMode,
+    pub start_market_cap_usd: Option<u64>, // Derives the virtual SOL reserve, constant product only
+    pub total_supply: Option<u64>,         // Defaults to the main state supply
+    pub decimals: Option<u8>,              // Defaults to the main state decimals
+    pub initial_buy_sol: Option<u64>,      // Creator's first buy, made before trading opens
+    pub min_tokens_out: u64,               // Minimum tokens from the initial buy
+    pub creator_fee_share: Option<u64>,    // Defaults to the main state share
 }
 
-pub fn create_pool(ctx: Context<ACreatePool>, input: CreatePoolInput) -> Result<()> {
-    let main_state = &mut ctx.accounts.main_state;
+pub fn create_pool(ctx: Context<ACreatePool>, mut input: CreatePoolInput) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
     require!(
         main_state.initialized.eq(&true),
         ThrustAppError::Uninitialized
     );
+    // Only the Token-2022 holding hook can vouch for how long a seller held
+    require!(
+        !matches!(input.tax_type, TaxType::DecayTax { .. }),
+        ThrustAppError::DecayTaxRequiresHoldingHook
+    );
+    let launch_profile = ctx.accounts.launch_profile.as_deref().map(|profile| &**profile);
+    let decimals = ctx.accounts.mint.decimals;
+    let params = PoolParams::resolve(&mut input, main_state, launch_profile, decimals)?;
 
     let mint_key = ctx.accounts.mint.key();
     let creator_key = ctx.accounts.creator.key();
@@ -35,38 +56,21 @@ pub fn create_pool(ctx: Context<ACreatePool>, input: CreatePoolInput) -> Result<
     let metadata_seeds = &[b"metadata", METADATA_PROGRAM_ID.as_ref(), mint_key.as_ref()];
     // let (metadata_key, _bump) = Pubkey::find_program_address(metadata_seeds, &METADATA_PROGRAM_ID);
 
-    let pool_state = &mut ctx.accounts.pool_state;
-    let user_state = &mut ctx.accounts.user_state;
-
-    // Set waiting room state
-    pool_state.waiting_room_state = match input.waiting_room_config {
-        Some(config) => WaitingRoomState::Enabled {
-            min_trades: config.min_trades,
-            max_participants: config.max_participants,
-            wallet_limit_percent: config.wallet_limit_percent,
-            closure_condition: config.closure_condition,
-            participants: 0,
-            total_buy_volume: 0,
-            closed: false,
-        },
-        None => WaitingRoomState::Disabled,
-    };
-
-    // Store referrer to user state, only 1 time store.
-    let default_pubkey = Pubkey::default();
-    if user_state.referrer == default_pubkey {
-        if let Some(referrer) = &ctx.accounts.referrer {
-            user_state.referrer = referrer.key();
-        }
+    if let Some(referrer_state) = ctx.accounts.referrer_state.as_deref_mut() {
+        referrer_state.bind(
+            &mut ctx.accounts.user_state,
+            &creator_key,
+            ctx.accounts.referrer_user_state.as_deref(),
+        )?;
     }
 
     // Define token metadata (name, symbol, URI, etc.)
     let metadata = DataV2 {
-        name: input.mint_name,      // Set Token Name
-        symbol: input.mint_symbol,  // Set Token Symbol
-        uri: input.mint_uri,        // Set Metadata URI
-        seller_fee_basis_points: 0, // No royalty
-        creators: None,             // No creators
+        name: input.mint_name.clone(),     // Set Token Name
+        symbol: input.mint_symbol.clone(), // Set Token Symbol
+        uri: input.mint_uri.clone(),       // Set Metadata URI
+        seller_fee_basis_points: 0,        // No royalty
+        creators: None,                    // No creators
         collection: None,
         uses: None,
     };
@@ -100,7 +104,7 @@ pub fn create_pool(ctx: Context<ACreatePool>, input: CreatePoolInput) -> Result<
     };
 
     let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-    token::mint_to(cpi_ctx, TOTAL_SUPPLY)?; // Mint 1 token (6 decimals)
+    token::mint_to(cpi_ctx, params.total_supply)?;
 
     // Revoke mint authority
     let cpi_accounts_mint = SetAuthority {
@@ -136,41 +140,255 @@ pub fn create_pool(ctx: Context<ACreatePool>, input: CreatePoolInput) -> Result<
         None,
     )?;
 
-    pool_state.owner = creator_key;
-    pool_state.mint = ctx.accounts.mint.to_account_info().key();
-    pool_state.start_trade_timestamp = input.trade_start_time;
+    fund_creator_vault(
+        &ctx.accounts.creator,
+        &ctx.accounts.creator_vault,
+        &ctx.accounts.system_program,
+    )?;
 
-    pool_state.real_base_reserves = main_state.init_real_base_reserves;
-    pool_state.real_quote_reserves = 0;
+    let (initial_buy_sol, min_tokens_out) = (input.initial_buy_sol, input.min_tokens_out);
+    ctx.accounts.pool_state.initialize(
+        &ctx.accounts.main_state,
+        input,
+        &params,
+        creator_key,
+        mint_key,
+    )?;
 
-    pool_state.virt_base_reserves = TOTAL_SUPPLY - main_state.init_real_base_reserves;
-    pool_state.virt_quote_reserves = main_state.init_virt_quote_reserves;
-    pool_state.konst = (pool_state.real_base_reserves as u128)
-        .checked_mul((pool_state.virt_quote_reserves + pool_state.real_quote_reserves) as u128)
-        .unwrap();
+    if let Some(sol_amount) = initial_buy_sol {
+        let (Some(reserve_pda), Some(creator_base_ata)) =
+            (&ctx.accounts.reserve_pda, &ctx.accounts.creator_base_ata)
+        else {
+            return err!(ThrustAppError::InitialBuyAccountsRequired);
+        };
+        let transfers = BuyTransfers {
+            buyer: ctx.accounts.creator.to_account_info(),
+            creator_vault: ctx.accounts.creator_vault.to_account_info(),
+            integrator: None,
+            mint: ctx.accounts.mint.to_account_info(),
+            decimals: params.decimals,
+            buyer_base_ata: creator_base_ata.to_account_info(),
+            reserve_pda: reserve_pda.to_account_info(),
+            reserver_base_ata: ctx.accounts.reserver_base_ata.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            remaining_accounts: vec![],
+            pool_bump: ctx.bumps.pool_state,
+            fee_override: ctx
+                .accounts
+                .fee_override
+                .as_deref()
+                .map(|fee_override| (**fee_override).clone()),
+        };
+        transfers.create_buyer_ata(&ctx.accounts.associated_token_program.to_account_info())?;
+        transfers.initial_buy(
+            &ctx.accounts.main_state,
+            &mut ctx.accounts.pool_state,
+            &mut ctx.accounts.user_state,
+            [
+                ctx.accounts.referrer_state.as_deref_mut(),
+                ctx.accounts.level2_referrer_state.as_deref_mut(),
+                ctx.accounts.level3_referrer_state.as_deref_mut(),
+            ],
+            sol_amount,
+            min_tokens_out,
+        )?;
+    }
 
-    let current_timestamp = Clock::get()?.unix_timestamp;
+    Ok(())
+}
 
-    pool_state.tax_type = input.tax_type;
-    pool_state.tax_start_timestamp = current_timestamp as u64;
+/// Makes the creator vault rent exempt, so trades can pay any creator fee into it.
+pub fn fund_creator_vault<'info>(
+    creator: &Signer<'info>,
+    creator_vault: &SystemAccount<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let rent_exempt = Rent::get()?.minimum_balance(0);
+    let amount = rent_exempt.saturating_sub(creator_vault.lamports());
+    if amount > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: creator.to_account_info(),
+                    to: creator_vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+    }
+    Ok(())
+}
 
-    emit!(CreateEvent {
-        creator: pool_state.owner,
-        mint: pool_state.mint,
-        base_reserves: pool_state.real_base_reserves + pool_state.virt_base_reserves,
-        quote_reserves: pool_state.virt_quote_reserves + pool_state.real_quote_reserves,
-        timestamp: current_timestamp
-    });
+/// Pool parameters merged from the launch profile, the main state defaults and the creator's input.
+pub struct PoolParams {
+    pub total_supply: u64,
+    pub decimals: u8,
+    pub init_real_base_reserves: u64,
+    pub init_virt_quote_reserves: u64,
+    pub real_sol_threshold: u64,
+    pub graduate_fee: u64,
+    pub trading_fee: Option<u64>,
+    pub creator_fee_share: u64,
+}
 
-    Ok(())
+impl PoolParams {
+    pub fn resolve(
+        input: &mut CreatePoolInput,
+        main_state: &MainState,
+        launch_profile: Option<&LaunchProfile>,
+        decimals: u8,
+    ) -> Result<Self> {
+        // A launch profile replaces the global defaults and the creator's curve and threshold
+        let mut init_real_base_reserves = main_state.init_real_base_reserves;
+        let mut init_virt_quote_reserves = main_state.init_virt_quote_reserves;
+        let mut graduate_fee = main_state.graduate_fee;
+        let mut trading_fee = None;
+        let real_sol_threshold = match launch_profile {
+            Some(launch_profile) => {
+                require!(
+                    launch_profile.enabled,
+                    ThrustAppError::LaunchProfileDisabled
+                );
+                require!(
+                    launch_profile.allows_tax_type(&input.tax_type),
+                    ThrustAppError::TaxTypeNotAllowed
+                );
+                input.curve_type = launch_profile.curve_type.clone();
+                if input.waiting_room_config.is_none() {
+                    input.waiting_room_config = launch_profile.waiting_room_config.clone();
+                }
+                init_real_base_reserves = launch_profile.init_real_base_reserves;
+                init_virt_quote_reserves = launch_profile.init_virt_quote_reserves;
+                graduate_fee = launch_profile.graduate_fee;
+                trading_fee = launch_profile.trading_fee;
+                launch_profile.real_sol_threshold
+            }
+            None => main_state.pool_real_sol_threshold(input.real_sol_threshold)?,
+        };
+        require!(input.curve_type.is_valid(), ThrustAppError::InvalidCurve);
+
+        // The reserve split is configured against the main state supply
+        let total_supply = input.total_supply.unwrap_or(main_state.total_token_supply);
+        main_state.validate_pool_token(total_supply, decimals)?;
+
+        Ok(Self {
+            total_supply,
+            decimals,
+            init_real_base_reserves: main_state
+                .scale_to_supply(init_real_base_reserves, total_supply)?,
+            init_virt_quote_reserves,
+            real_sol_threshold,
+            graduate_fee,
+            trading_fee,
+            creator_fee_share: main_state.pool_creator_fee_share(input.creator_fee_share)?,
+        })
+    }
+}
+
+impl PoolState {
+    /// Sets up a freshly created pool once its supply is minted to the reserve.
+    pub fn initialize(
+        &mut self,
+        main_state: &MainState,
+        input: CreatePoolInput,
+        params: &PoolParams,
+        creator: Pubkey,
+        mint: Pubkey,
+    ) -> Result<()> {
+        // Set waiting room state
+        self.waiting_room_state = match input.waiting_room_config {
+            Some(config) => WaitingRoomState::Enabled {
+                min_trades: config.min_trades,
+                max_participants: config.max_participants,
+                wallet_limit_percent: config.wallet_limit_percent,
+                closure_condition: config.closure_condition,
+                participants: 0,
+                total_buy_volume: 0,
+                closed: false,
+            },
+            None => WaitingRoomState::Disabled,
+        };
+
+        self.owner = creator;
+        self.mint = mint;
+        self.start_trade_timestamp = input.trade_start_time;
+
+        self.real_base_reserves = params.init_real_base_reserves;
+        self.init_real_base_reserves = params.init_real_base_reserves;
+        self.real_quote_reserves = 0;
+        self.curve_type = input.curve_type;
+        self.real_sol_threshold = params.real_sol_threshold;
+        self.graduate_fee = params.graduate_fee;
+        self.trading_fee = params.trading_fee;
+        self.creator_fee_share = params.creator_fee_share;
+        self.total_supply = params.total_supply;
+        self.decimals = params.decimals;
+
+        self.virt_base_reserves = params
+            .total_supply
+            .checked_sub(params.init_real_base_reserves)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        self.virt_quote_reserves = match input.start_market_cap_usd {
+            Some(start_market_cap_usd) => {
+                require!(
+                    self.curve_type == CurveType::ConstantProduct,
+                    ThrustAppError::InvalidGraduationMode
+                );
+                // Starting price is virt_quote / total_supply, so the SOL market cap is virt_quote
+                let virt_quote_reserves =
+                    calculate_sol_from_usd(start_market_cap_usd, main_state.sol_price)?;
+                require!(virt_quote_reserves > 0, ThrustAppError::InvalidGraduationMode);
+                virt_quote_reserves
+            }
+            None => params.init_virt_quote_reserves,
+        };
+        self.konst = (self.real_base_reserves as u128)
+            .checked_mul(self.quote_reserves()? as u128)
+            .ok_or(ThrustAppError::MathOverflow)?;
+
+        self.graduation_mode = input.graduation_mode;
+        if let GraduationMode::MarketCapUsd { target_usd } = self.graduation_mode {
+            require!(main_state.sol_price > 0, ThrustAppError::SolPriceNotSet);
+            let start_market_cap_usd = self.market_cap_usd(main_state.sol_price)?;
+            require!(
+                target_usd > start_market_cap_usd,
+                ThrustAppError::InvalidGraduationMode
+            );
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        self.tax_type = input.tax_type;
+        self.tax_start_timestamp = current_timestamp as u64;
+
+        emit!(CreateEvent {
+            creator: self.owner,
+            mint: self.mint,
+            base_reserves: self.base_reserves()?,
+            quote_reserves: self.quote_reserves()?,
+            timestamp: current_timestamp
+        });
+
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
+#[instruction(input: CreatePoolInput)]
 pub struct ACreatePool<'info> {
+    #[account(
+        mut,
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
     #[account(
         init,
         payer = creator,
-        mint::decimals = 6,
+        mint::decimals = input.decimals.unwrap_or(main_state.token_decimals),
         mint::authority = creator
     )]
     pub mint: Account<'info, Mint>,
@@ -182,13 +400,6 @@ pub struct ACreatePool<'info> {
     #[account(mut)]
     pub metadata_account: UncheckedAccount<'info>,
 
-    #[account(
-        mut,
-        seeds = [MainState::PREFIX_SEED],
-        bump,
-    )]
-    pub main_state: Box<Account<'info, MainState>>,
-
     #[account(
         init,
         payer = creator,
@@ -213,7 +424,46 @@ pub struct ACreatePool<'info> {
     )]
     pub user_state: Box<Account<'info, UserState>>,
 
-    pub referrer: Option<AccountInfo<'info>>,
+    /// Referrer's reward vault, for trades made through a referral
+    #[account(
+        mut,
+        seeds = [
+            ReferrerState::PREFIX_SEED,
+            referral_code
+                .as_ref()
+                .map_or(referrer_state.referrer, |referral_code| referral_code.referrer)
+                .as_ref(),
+        ],
+        bump,
+    )]
+    pub referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Reward vault of the referrer's referrer, for multi-level referral rewards
+    #[account(
+        mut,
+        seeds = [ReferrerState::PREFIX_SEED, level2_referrer_state.referrer.as_ref()],
+        bump,
+    )]
+    pub level2_referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Reward vault of the third referral level
+    #[account(
+        mut,
+        seeds = [ReferrerState::PREFIX_SEED, level3_referrer_state.referrer.as_ref()],
+        bump,
+    )]
+    pub level3_referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Referrer's own user state, needed when the referrer gets bound to the creator
+    pub referrer_user_state: Option<Box<Account<'info, UserState>>>,
+
+    /// Referral code the launch came through, `referrer_state` is derived from its referrer
+    #[account(
+        constraint = referrer_state.is_some() @ ThrustAppError::ReferrerStateRequired,
+    )]
+    pub referral_code: Option<Box<Account<'info, ReferralCode>>>,
+
+    pub launch_profile: Option<Box<Account<'info, LaunchProfile>>>,
 
     #[account(
         init,
@@ -222,6 +472,38 @@ pub struct ACreatePool<'info> {
         associated_token::authority = pool_state,
     )]
     pub reserver_base_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [
+            CREATOR_VAULT_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub creator_vault: SystemAccount<'info>,
+
+    /// Required for an initial buy
+    #[account(
+        mut,
+        seeds = [
+            RESERVE_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub reserve_pda: Option<SystemAccount<'info>>,
+
+    /// CHECK: Creator's associated token account, created for an initial buy
+    #[account(mut)]
+    pub creator_base_ata: Option<UncheckedAccount<'info>>,
+
+    /// Creator's negotiated trading fee, applied to the initial buy
+    #[account(
+        seeds = [FeeOverride::PREFIX_SEED, creator.key().as_ref()],
+        bump,
+    )]
+    pub fee_override: Option<Box<Account<'info, FeeOverride>>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Program<'info, Token>,
     /// CHECK: Ensure valid Metadata Program Account
@@ -0,0 +1,387 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::program::invoke,
+    system_program::{self, CreateAccount},
+};
+use anchor_spl::{
+    associated_token::{self, AssociatedToken, Create},
+    token_2022::{
+        self,
+        spl_token_2022::{
+            extension::{metadata_pointer, transfer_hook, ExtensionType},
+            instruction::AuthorityType,
+            state::Mint,
+        },
+        InitializeMint2, MintTo, SetAuthority, Token2022,
+    },
+};
+use holding_hook::{cpi::accounts::AInitializeExtraAccountMetaList, program::HoldingHook};
+use spl_token_metadata_interface::{instruction as token_metadata, state::TokenMetadata};
+
+use crate::{
+    constants::{CREATOR_VAULT_SEED, RESERVE_SEED},
+    error::ThrustAppError,
+    fund_creator_vault, BuyTransfers, CreatePoolInput, FeeOverride, LaunchProfile, MainState,
+    PoolParams, PoolState, ReferralCode, ReferrerState, TaxType, UserState,
+};
+
+/// Same as `create_pool`, but launches a Token-2022 mint carrying its own metadata
+/// through the metadata-pointer and token-metadata extensions instead of Metaplex.
+/// Decay tax pools also get the holding hook, so holding time is recorded on chain.
+pub fn create_pool_2022<'info>(
+    ctx: Context<'_, '_, '_, 'info, ACreatePool2022<'info>>,
+    mut input: CreatePoolInput,
+) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(
+        main_state.initialized.eq(&true),
+        ThrustAppError::Uninitialized
+    );
+    let launch_profile = ctx.accounts.launch_profile.as_deref().map(|profile| &**profile);
+    let decimals = input.decimals.unwrap_or(main_state.token_decimals);
+    let params = PoolParams::resolve(&mut input, main_state, launch_profile, decimals)?;
+
+    let mint_key = ctx.accounts.mint.key();
+    let creator_key = ctx.accounts.creator.key();
+    let mint = ctx.accounts.mint.to_account_info();
+    let creator = ctx.accounts.creator.to_account_info();
+    let token_program = ctx.accounts.token_program.to_account_info();
+
+    if let Some(referrer_state) = ctx.accounts.referrer_state.as_deref_mut() {
+        referrer_state.bind(
+            &mut ctx.accounts.user_state,
+            &creator_key,
+            ctx.accounts.referrer_user_state.as_deref(),
+        )?;
+    }
+
+    // Create the mint with room for the metadata pointer, funded for the metadata
+    // that the token-metadata extension reallocates into it
+    let metadata = TokenMetadata {
+        update_authority: Some(creator_key).try_into()?,
+        mint: mint_key,
+        name: input.mint_name.clone(),
+        symbol: input.mint_symbol.clone(),
+        uri: input.mint_uri.clone(),
+        additional_metadata: vec![],
+    };
+    let holding_hook = matches!(input.tax_type, TaxType::DecayTax { .. });
+    let mut extensions = vec![ExtensionType::MetadataPointer];
+    if holding_hook {
+        extensions.push(ExtensionType::TransferHook);
+    }
+    let mint_space = ExtensionType::try_calculate_account_len::<Mint>(&extensions)?;
+    let lamports = Rent::get()?.minimum_balance(mint_space + metadata.tlv_size_of()?);
+    system_program::create_account(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            CreateAccount {
+                from: creator.clone(),
+                to: mint.clone(),
+            },
+        ),
+        lamports,
+        mint_space as u64,
+        &token_2022::ID,
+    )?;
+
+    // Point the metadata at the mint itself
+    invoke(
+        &metadata_pointer::instruction::initialize(
+            &token_2022::ID,
+            &mint_key,
+            None,
+            Some(mint_key),
+        )?,
+        std::slice::from_ref(&mint),
+    )?;
+
+    if holding_hook {
+        invoke(
+            &transfer_hook::instruction::initialize(
+                &token_2022::ID,
+                &mint_key,
+                None,
+                Some(holding_hook::ID),
+            )?,
+            std::slice::from_ref(&mint),
+        )?;
+    }
+
+    token_2022::initialize_mint2(
+        CpiContext::new(
+            token_program.clone(),
+            InitializeMint2 { mint: mint.clone() },
+        ),
+        decimals,
+        &creator_key,
+        None, // No freeze authority
+    )?;
+
+    if holding_hook {
+        let (Some(hook_program), Some(extra_account_meta_list)) = (
+            &ctx.accounts.holding_hook_program,
+            &ctx.accounts.extra_account_meta_list,
+        ) else {
+            return err!(ThrustAppError::HoldingHookAccountsRequired);
+        };
+        holding_hook::cpi::initialize_extra_account_meta_list(CpiContext::new(
+            hook_program.to_account_info(),
+            AInitializeExtraAccountMetaList {
+                payer: creator.clone(),
+                extra_account_meta_list: extra_account_meta_list.to_account_info(),
+                mint: mint.clone(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+        ))?;
+    }
+
+    invoke(
+        &token_metadata::initialize(
+            &token_2022::ID,
+            &mint_key,
+            &creator_key,
+            &mint_key,
+            &creator_key,
+            metadata.name,
+            metadata.symbol,
+            metadata.uri,
+        ),
+        &[mint.clone(), creator.clone()],
+    )?;
+
+    // Make the metadata immutable
+    invoke(
+        &token_metadata::update_authority(
+            &token_2022::ID,
+            &mint_key,
+            &creator_key,
+            None.try_into()?,
+        ),
+        &[mint.clone(), creator.clone()],
+    )?;
+
+    associated_token::create(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        Create {
+            payer: creator.clone(),
+            associated_token: ctx.accounts.reserver_base_ata.to_account_info(),
+            authority: ctx.accounts.pool_state.to_account_info(),
+            mint: mint.clone(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: token_program.clone(),
+        },
+    ))?;
+
+    // Mint tokens
+    token_2022::mint_to(
+        CpiContext::new(
+            token_program.clone(),
+            MintTo {
+                mint: mint.clone(),
+                to: ctx.accounts.reserver_base_ata.to_account_info(),
+                authority: creator.clone(),
+            },
+        ),
+        params.total_supply,
+    )?;
+
+    // Revoke mint authority
+    token_2022::set_authority(
+        CpiContext::new(
+            token_program,
+            SetAuthority {
+                account_or_mint: mint,
+                current_authority: creator,
+            },
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+
+    fund_creator_vault(
+        &ctx.accounts.creator,
+        &ctx.accounts.creator_vault,
+        &ctx.accounts.system_program,
+    )?;
+
+    let (initial_buy_sol, min_tokens_out) = (input.initial_buy_sol, input.min_tokens_out);
+    ctx.accounts.pool_state.holding_hook = holding_hook;
+    ctx.accounts.pool_state.initialize(
+        &ctx.accounts.main_state,
+        input,
+        &params,
+        creator_key,
+        mint_key,
+    )?;
+
+    if let Some(sol_amount) = initial_buy_sol {
+        let (Some(reserve_pda), Some(creator_base_ata)) =
+            (&ctx.accounts.reserve_pda, &ctx.accounts.creator_base_ata)
+        else {
+            return err!(ThrustAppError::InitialBuyAccountsRequired);
+        };
+        let transfers = BuyTransfers {
+            buyer: ctx.accounts.creator.to_account_info(),
+            creator_vault: ctx.accounts.creator_vault.to_account_info(),
+            integrator: None,
+            mint: ctx.accounts.mint.to_account_info(),
+            decimals: params.decimals,
+            buyer_base_ata: creator_base_ata.to_account_info(),
+            reserve_pda: reserve_pda.to_account_info(),
+            reserver_base_ata: ctx.accounts.reserver_base_ata.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            remaining_accounts: ctx.remaining_accounts.to_vec(),
+            pool_bump: ctx.bumps.pool_state,
+            fee_override: ctx
+                .accounts
+                .fee_override
+                .as_deref()
+                .map(|fee_override| (**fee_override).clone()),
+        };
+        transfers.create_buyer_ata(&ctx.accounts.associated_token_program.to_account_info())?;
+        transfers.initial_buy(
+            &ctx.accounts.main_state,
+            &mut ctx.accounts.pool_state,
+            &mut ctx.accounts.user_state,
+            [
+                ctx.accounts.referrer_state.as_deref_mut(),
+                ctx.accounts.level2_referrer_state.as_deref_mut(),
+                ctx.accounts.level3_referrer_state.as_deref_mut(),
+            ],
+            sol_amount,
+            min_tokens_out,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ACreatePool2022<'info> {
+    #[account(
+        mut,
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    /// Created and initialized by the instruction
+    #[account(mut)]
+    pub mint: Signer<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds =[
+            PoolState::PREFIX_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+        space = 8 + PoolState::MAX_SIZE
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + UserState::MAX_SIZE,
+        seeds =[
+            UserState::PREFIX_SEED,
+            creator.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_state: Box<Account<'info, UserState>>,
+
+    /// Referrer's reward vault, for trades made through a referral
+    #[account(
+        mut,
+        seeds = [
+            ReferrerState::PREFIX_SEED,
+            referral_code
+                .as_ref()
+                .map_or(referrer_state.referrer, |referral_code| referral_code.referrer)
+                .as_ref(),
+        ],
+        bump,
+    )]
+    pub referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Reward vault of the referrer's referrer, for multi-level referral rewards
+    #[account(
+        mut,
+        seeds = [ReferrerState::PREFIX_SEED, level2_referrer_state.referrer.as_ref()],
+        bump,
+    )]
+    pub level2_referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Reward vault of the third referral level
+    #[account(
+        mut,
+        seeds = [ReferrerState::PREFIX_SEED, level3_referrer_state.referrer.as_ref()],
+        bump,
+    )]
+    pub level3_referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Referrer's own user state, needed when the referrer gets bound to the creator
+    pub referrer_user_state: Option<Box<Account<'info, UserState>>>,
+
+    /// Referral code the launch came through, `referrer_state` is derived from its referrer
+    #[account(
+        constraint = referrer_state.is_some() @ ThrustAppError::ReferrerStateRequired,
+    )]
+    pub referral_code: Option<Box<Account<'info, ReferralCode>>>,
+
+    pub launch_profile: Option<Box<Account<'info, LaunchProfile>>>,
+
+    /// CHECK: Pool's associated token account, created by the instruction once the mint exists
+    #[account(mut)]
+    pub reserver_base_ata: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            CREATOR_VAULT_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub creator_vault: SystemAccount<'info>,
+
+    /// Required for an initial buy
+    #[account(
+        mut,
+        seeds = [
+            RESERVE_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub reserve_pda: Option<SystemAccount<'info>>,
+
+    /// CHECK: Creator's associated token account, created for an initial buy
+    #[account(mut)]
+    pub creator_base_ata: Option<UncheckedAccount<'info>>,
+
+    /// Creator's negotiated trading fee, applied to the initial buy
+    #[account(
+        seeds = [FeeOverride::PREFIX_SEED, creator.key().as_ref()],
+        bump,
+    )]
+    pub fee_override: Option<Box<Account<'info, FeeOverride>>>,
+
+    /// Required for decay tax pools
+    pub holding_hook_program: Option<Program<'info, HoldingHook>>,
+
+    /// CHECK: Hook validation account, created and checked by the holding hook
+    #[account(mut)]
+    pub extra_account_meta_list: Option<UncheckedAccount<'info>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
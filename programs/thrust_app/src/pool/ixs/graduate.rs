@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use crate::{
+    constants::RESERVE_SEED, error::ThrustAppError, FeeVault, LiquidityMigration, MainState,
+    PoolState,
+};
+
+/// Permissionless `migrate`: once a pool completes anyone can move its liquidity to the
+/// AMM configured in the main state, earning the graduation bounty held back from the
+/// graduation fee. Everything the migration does, its target included, is derived from
+/// the pool and main state, so the caller can't influence it.
+pub fn graduate<'info>(ctx: Context<'_, '_, '_, 'info, AGraduate<'info>>) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(
+        main_state.initialized.eq(&true),
+        ThrustAppError::Uninitialized
+    );
+    require!(
+        main_state.amm_program != Pubkey::default(),
+        ThrustAppError::AmmNotConfigured
+    );
+
+    let migration = LiquidityMigration {
+        keeper: ctx.accounts.keeper.to_account_info(),
+        fee_vault: ctx.accounts.fee_vault.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        reserve_pda: ctx.accounts.reserve_pda.to_account_info(),
+        reserver_base_ata: ctx.accounts.reserver_base_ata.to_account_info(),
+        amm_pool: ctx.accounts.amm_pool.to_account_info(),
+        amm_lp_mint: ctx.accounts.amm_lp_mint.to_account_info(),
+        amm_base_vault: ctx.accounts.amm_base_vault.to_account_info(),
+        pool_lp_account: ctx.accounts.pool_lp_account.to_account_info(),
+        amm_program: ctx.accounts.amm_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        lp_token_program: ctx.accounts.lp_token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        remaining_accounts: ctx.remaining_accounts.to_vec(),
+        amm_init_discriminator: main_state.amm_init_discriminator,
+        amm_init_accounts: main_state.amm_init_accounts,
+        pool_bump: ctx.bumps.pool_state,
+        reserve_bump: ctx.bumps.reserve_pda,
+    };
+    migration.migrate(&mut ctx.accounts.pool_state)
+}
+
+#[derive(Accounts)]
+pub struct AGraduate<'info> {
+    /// Anyone; pays the rent of the AMM accounts and receives the graduation bounty
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        mut,
+        seeds = [FeeVault::PREFIX_SEED],
+        bump,
+    )]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PoolState::PREFIX_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [
+            RESERVE_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = pool_state,
+    )]
+    pub reserver_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Created and checked by the AMM
+    #[account(mut)]
+    pub amm_pool: UncheckedAccount<'info>,
+
+    /// CHECK: Created and checked by the AMM
+    #[account(mut)]
+    pub amm_lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Created and checked by the AMM
+    #[account(mut)]
+    pub amm_base_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Pool's LP token account, created by the AMM and emptied by the burn
+    #[account(mut)]
+    pub pool_lp_account: UncheckedAccount<'info>,
+
+    /// CHECK: The AMM configured in the main state, see `LiquidityMigration`
+    #[account(
+        executable,
+        address = main_state.amm_program @ ThrustAppError::InvalidAmmProgram,
+    )]
+    pub amm_program: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub lp_token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
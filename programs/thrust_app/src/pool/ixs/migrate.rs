@@ -0,0 +1,309 @@
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Burn, Token},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use crate::{
+    constants::RESERVE_SEED, error::ThrustAppError, transfer_protocol_fees, AmmInitAccount,
+    FeeVault, MainState, MigrateEvent, PoolState,
+};
+
+/// Lists a completed pool on the configured AMM instead of withdrawing it, see
+/// `LiquidityMigration`. `graduate` does the same for anyone once the pool completes.
+pub fn migrate<'info>(ctx: Context<'_, '_, '_, 'info, AMigrate<'info>>) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(
+        main_state.initialized.eq(&true),
+        ThrustAppError::Uninitialized
+    );
+    require!(
+        main_state.amm_program != Pubkey::default(),
+        ThrustAppError::AmmNotConfigured
+    );
+
+    let migration = LiquidityMigration {
+        keeper: ctx.accounts.owner.to_account_info(),
+        fee_vault: ctx.accounts.fee_vault.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        reserve_pda: ctx.accounts.reserve_pda.to_account_info(),
+        reserver_base_ata: ctx.accounts.reserver_base_ata.to_account_info(),
+        amm_pool: ctx.accounts.amm_pool.to_account_info(),
+        amm_lp_mint: ctx.accounts.amm_lp_mint.to_account_info(),
+        amm_base_vault: ctx.accounts.amm_base_vault.to_account_info(),
+        pool_lp_account: ctx.accounts.pool_lp_account.to_account_info(),
+        amm_program: ctx.accounts.amm_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        lp_token_program: ctx.accounts.lp_token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        remaining_accounts: ctx.remaining_accounts.to_vec(),
+        amm_init_discriminator: main_state.amm_init_discriminator,
+        amm_init_accounts: main_state.amm_init_accounts,
+        pool_bump: ctx.bumps.pool_state,
+        reserve_bump: ctx.bumps.reserve_pda,
+    };
+    migration.migrate(&mut ctx.accounts.pool_state)
+}
+
+/// Accounts moving a completed pool's liquidity to the AMM, shared by `migrate` and `graduate`.
+pub struct LiquidityMigration<'info> {
+    pub keeper: AccountInfo<'info>, // Pays the AMM accounts' rent and receives the bounty
+    pub fee_vault: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub reserve_pda: AccountInfo<'info>,
+    pub reserver_base_ata: AccountInfo<'info>,
+    pub amm_pool: AccountInfo<'info>,
+    pub amm_lp_mint: AccountInfo<'info>,
+    pub amm_base_vault: AccountInfo<'info>,
+    pub pool_lp_account: AccountInfo<'info>,
+    pub amm_program: AccountInfo<'info>, // Checked against `MainState::amm_program`
+    pub associated_token_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub lp_token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub remaining_accounts: Vec<AccountInfo<'info>>, // Transfer hook accounts
+    pub amm_init_discriminator: [u8; 8],
+    pub amm_init_accounts: [Option<AmmInitAccount>; 16],
+    pub pool_bump: u8,
+    pub reserve_bump: u8,
+}
+
+impl<'info> LiquidityMigration<'info> {
+    /// The keeper gets the graduation bounty, the protocol fees go to the fee vault and
+    /// the reserve's remaining SOL and the tokens matching it at the closing price seed
+    /// the AMM pool straight from the program PDAs. Tokens above that ratio stay locked
+    /// in the pool's token account, and the LP tokens received are burned so the
+    /// liquidity stays locked too.
+    pub fn migrate(self, pool_state: &mut Account<'info, PoolState>) -> Result<()> {
+        require!(
+            pool_state.complete.eq(&true),
+            ThrustAppError::BondingCurveIncomplete
+        );
+        require!(
+            pool_state.withdrawn.eq(&false),
+            ThrustAppError::AlreadyWithdrawn
+        );
+        require!(pool_state.amm.is_none(), ThrustAppError::AmmActive);
+        pool_state.withdrawn = true;
+
+        let mint_key = pool_state.mint;
+        let pool_signer_seeds: &[&[u8]] =
+            &[PoolState::PREFIX_SEED, mint_key.as_ref(), &[self.pool_bump]];
+        let reserve_signer_seeds: &[&[u8]] =
+            &[RESERVE_SEED, mint_key.as_ref(), &[self.reserve_bump]];
+
+        let bounty = pool_state.graduation_bounty;
+        if bounty > 0 {
+            pool_state.graduation_bounty = 0;
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    self.system_program.clone(),
+                    system_program::Transfer {
+                        from: self.reserve_pda.clone(),
+                        to: self.keeper.clone(),
+                    },
+                    &[reserve_signer_seeds],
+                ),
+                bounty,
+            )?;
+        }
+
+        // Protocol fees and the graduation fee don't go into the AMM
+        transfer_protocol_fees(
+            pool_state,
+            self.reserve_pda.clone(),
+            self.fee_vault.clone(),
+            self.system_program.clone(),
+            self.reserve_bump,
+        )?;
+
+        let quote_amount = self.reserve_pda.lamports();
+        let base_amount = pool_state.base_at_closing_price(quote_amount)?;
+        let (instruction, account_infos) = self.initialize_pool_instruction(
+            &pool_state.to_account_info(),
+            base_amount,
+            quote_amount,
+        );
+        invoke_signed(
+            &instruction,
+            &account_infos,
+            &[pool_signer_seeds, reserve_signer_seeds],
+        )?;
+
+        let lp_burned = token::accessor::amount(&self.pool_lp_account)?;
+        token::burn(
+            CpiContext::new_with_signer(
+                self.lp_token_program,
+                Burn {
+                    mint: self.amm_lp_mint,
+                    from: self.pool_lp_account,
+                    authority: pool_state.to_account_info(),
+                },
+                &[pool_signer_seeds],
+            ),
+            lp_burned,
+        )?;
+
+        let amm_pool = self.amm_pool.key();
+        pool_state.amm_pool = Some(amm_pool);
+
+        emit!(MigrateEvent {
+            mint: mint_key,
+            amm_pool,
+            base_amount,
+            quote_amount,
+            lp_burned,
+            keeper: self.keeper.key(),
+            bounty,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Pool creation on the configured AMM. Its data is `amm_init_discriminator` followed
+    /// by the base and quote amounts as little endian u64s, and it takes the accounts
+    /// listed in `amm_init_accounts`, then the transfer hook accounts.
+    fn initialize_pool_instruction(
+        &self,
+        pool_authority: &AccountInfo<'info>,
+        base_amount: u64,
+        quote_amount: u64,
+    ) -> (Instruction, Vec<AccountInfo<'info>>) {
+        let mut data = self.amm_init_discriminator.to_vec();
+        data.extend_from_slice(&base_amount.to_le_bytes());
+        data.extend_from_slice(&quote_amount.to_le_bytes());
+
+        let mut accounts = vec![];
+        let mut account_infos = vec![];
+        for account in self.amm_init_accounts.iter().flatten() {
+            let info = self.amm_init_account_info(account, pool_authority);
+            accounts.push(match account.is_writable() {
+                true => AccountMeta::new(info.key(), account.is_signer()),
+                false => AccountMeta::new_readonly(info.key(), account.is_signer()),
+            });
+            account_infos.push(info);
+        }
+        for info in &self.remaining_accounts {
+            accounts.push(AccountMeta {
+                pubkey: info.key(),
+                is_signer: info.is_signer,
+                is_writable: info.is_writable,
+            });
+            account_infos.push(info.clone());
+        }
+        account_infos.push(self.amm_program.clone());
+
+        let instruction = Instruction {
+            program_id: self.amm_program.key(),
+            accounts,
+            data,
+        };
+        (instruction, account_infos)
+    }
+
+    fn amm_init_account_info(
+        &self,
+        account: &AmmInitAccount,
+        pool_authority: &AccountInfo<'info>,
+    ) -> AccountInfo<'info> {
+        match account {
+            AmmInitAccount::Payer => self.keeper.clone(),
+            AmmInitAccount::Reserve => self.reserve_pda.clone(),
+            AmmInitAccount::PoolAuthority | AmmInitAccount::LpOwner => pool_authority.clone(),
+            AmmInitAccount::BaseMint => self.mint.clone(),
+            AmmInitAccount::ReserveBaseAccount => self.reserver_base_ata.clone(),
+            AmmInitAccount::AmmPool => self.amm_pool.clone(),
+            AmmInitAccount::AmmLpMint => self.amm_lp_mint.clone(),
+            AmmInitAccount::AmmBaseVault => self.amm_base_vault.clone(),
+            AmmInitAccount::PoolLpAccount => self.pool_lp_account.clone(),
+            AmmInitAccount::BaseTokenProgram => self.token_program.clone(),
+            AmmInitAccount::LpTokenProgram => self.lp_token_program.clone(),
+            AmmInitAccount::AssociatedTokenProgram => self.associated_token_program.clone(),
+            AmmInitAccount::SystemProgram => self.system_program.clone(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct AMigrate<'info> {
+    /// Pays the rent of the AMM accounts and receives the graduation bounty
+    #[account(mut, address = main_state.owner @ ThrustAppError::Unauthorised)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+        has_one = owner,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        mut,
+        seeds = [FeeVault::PREFIX_SEED],
+        bump,
+    )]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PoolState::PREFIX_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [
+            RESERVE_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = pool_state,
+    )]
+    pub reserver_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Created and checked by the AMM
+    #[account(mut)]
+    pub amm_pool: UncheckedAccount<'info>,
+
+    /// CHECK: Created and checked by the AMM
+    #[account(mut)]
+    pub amm_lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Created and checked by the AMM
+    #[account(mut)]
+    pub amm_base_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Pool's LP token account, created by the AMM and emptied by the burn
+    #[account(mut)]
+    pub pool_lp_account: UncheckedAccount<'info>,
+
+    /// CHECK: The AMM configured in the main state, see `LiquidityMigration`
+    #[account(
+        executable,
+        address = main_state.amm_program @ ThrustAppError::InvalidAmmProgram,
+    )]
+    pub amm_program: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub lp_token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
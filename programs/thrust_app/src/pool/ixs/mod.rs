@@ -1,11 +1,32 @@
 pub mod create_pool;
 pub use create_pool::*;
 
+pub mod create_pool_2022;
+pub use create_pool_2022::*;
+
 pub mod buy;
 pub use buy::*;
 
+pub mod buy_exact_out;
+pub use buy_exact_out::*;
+
 pub mod sell;
 pub use sell::*;
 
 pub mod withdraw;
 pub use withdraw::*;
+
+pub mod migrate;
+pub use migrate::*;
+
+pub mod graduate;
+pub use graduate::*;
+
+pub mod quote;
+pub use quote::*;
+
+pub mod claim_creator_fees;
+pub use claim_creator_fees::*;
+
+pub mod sweep_protocol_fees;
+pub use sweep_protocol_fees::*;
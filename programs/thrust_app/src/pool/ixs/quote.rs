@@ -0,0 +1,222 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+use holding_hook::state::HolderState;
+use crate::{
+    error::ThrustAppError,
+    utils::calculate_trading_fee,
+    BuyInput, FeeOverride, MainState, PoolState, ReferrerState, SellInput, UserState,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TradeQuote {
+    pub token_amount: u64,        // Tokens received on buy, tokens sold on sell
+    pub sol_amount: u64,          // SOL paid on buy (fee included), received on sell (after fee and tax)
+    pub unfilled_sol_amount: u64, // Part of a buy left unfilled at the graduation threshold
+    pub fee: u64,                 // Platform trading fee
+    pub tax: u64,                 // Sell tax charged above the trading fee
+    pub referral_reward: u64,     // Part of the fee paid to the referrer
+    pub creator_fee: u64,         // Part of the fee paid to the creator vault
+    pub integrator_fee: u64,      // Charged on top of the fee for the integrator
+    pub fee_tier: Option<u8>,     // Volume tier that lowered the trading fee, if any
+    pub price: u64,               // Post-trade price, see `PoolState::current_price`
+    pub graduation_progress: u64, // Post-trade progress, see `PoolState::graduation_progress`
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PoolInfo {
+    pub mint: Pubkey,
+    pub base_reserves: u64,
+    pub quote_reserves: u64,
+    pub real_base_reserves: u64,
+    pub real_quote_reserves: u64,
+    pub price: u64,
+    pub graduation_progress: u64,
+    pub market_cap_usd: u64,
+    pub real_sol_threshold: u64,
+    pub complete: bool,
+}
+
+pub fn quote_buy(ctx: Context<AQuote>, input: BuyInput) -> Result<TradeQuote> {
+    let main_state = &ctx.accounts.main_state;
+    require!(
+        main_state.initialized.eq(&true),
+        ThrustAppError::Uninitialized
+    );
+    require!(
+        ctx.accounts.pool_state.complete.eq(&false),
+        ThrustAppError::BondingCurveComplete
+    );
+
+    main_state.validate_integrator_fee(input.integrator_fee)?;
+
+    // Run the trade on a copy so nothing is persisted
+    let mut pool_state = (**ctx.accounts.pool_state).clone();
+    let user_state = ctx.accounts.user_state();
+    let (trading_fee, fee_tier) =
+        pool_state.user_trading_fee(main_state, &user_state, ctx.accounts.fee_override());
+    let fill = pool_state.apply_buy(input.amount, trading_fee, input.integrator_fee)?;
+
+    Ok(TradeQuote {
+        token_amount: fill.output_amount,
+        sol_amount: fill.sol_amount()?,
+        unfilled_sol_amount: fill.unfilled_amount,
+        fee: fill.fee,
+        tax: 0,
+        referral_reward: ctx.accounts.referral_reward(fill.fee)?,
+        creator_fee: pool_state.accrue_creator_fee(fill.fee)?,
+        integrator_fee: fill.integrator_fee,
+        fee_tier,
+        price: pool_state.current_price()?,
+        graduation_progress: pool_state.graduation_progress(main_state)?,
+    })
+}
+
+pub fn quote_sell(ctx: Context<AQuote>, input: SellInput) -> Result<TradeQuote> {
+    let main_state = &ctx.accounts.main_state;
+    require!(
+        main_state.initialized.eq(&true),
+        ThrustAppError::Uninitialized
+    );
+    require!(
+        ctx.accounts.pool_state.complete.eq(&false),
+        ThrustAppError::BondingCurveComplete
+    );
+    main_state.validate_integrator_fee(input.integrator_fee)?;
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+
+    let mut pool_state = (**ctx.accounts.pool_state).clone();
+    let quote_amount = pool_state.compute_receivable_amount_on_sell(input.amount)?;
+
+    let user_state = ctx.accounts.user_state();
+    let seller_balance = match &ctx.accounts.user_base_ata {
+        Some(ata) => ata.amount,
+        None => 0,
+    };
+    let fee_rate = pool_state.sell_fee_rate(
+        main_state,
+        &user_state,
+        ctx.accounts.fee_override(),
+        quote_amount,
+        seller_balance,
+        current_timestamp,
+        pool_state.last_received_time(
+            ctx.accounts.holder_state.as_deref().map(|holder| &**holder),
+            current_timestamp,
+        )?,
+    )?;
+    let total_fee = calculate_trading_fee(fee_rate, quote_amount)?;
+    let (trading_fee, fee_tier) =
+        pool_state.user_trading_fee(main_state, &user_state, ctx.accounts.fee_override());
+    let fee = total_fee.min(calculate_trading_fee(trading_fee, quote_amount)?);
+    let integrator_fee = calculate_trading_fee(input.integrator_fee, quote_amount)?;
+
+    Ok(TradeQuote {
+        token_amount: input.amount,
+        sol_amount: quote_amount
+            .checked_sub(total_fee)
+            .and_then(|amount| amount.checked_sub(integrator_fee))
+            .ok_or(ThrustAppError::MathOverflow)?,
+        unfilled_sol_amount: 0,
+        fee,
+        tax: total_fee - fee,
+        referral_reward: ctx.accounts.referral_reward(total_fee)?,
+        creator_fee: pool_state.accrue_creator_fee(total_fee)?,
+        integrator_fee,
+        fee_tier,
+        price: pool_state.current_price()?,
+        graduation_progress: pool_state.graduation_progress(main_state)?,
+    })
+}
+
+pub fn pool_info(ctx: Context<AQuote>) -> Result<PoolInfo> {
+    let main_state = &ctx.accounts.main_state;
+    let pool_state = &ctx.accounts.pool_state;
+
+    Ok(PoolInfo {
+        mint: pool_state.mint,
+        base_reserves: pool_state.base_reserves()?,
+        quote_reserves: pool_state.quote_reserves()?,
+        real_base_reserves: pool_state.real_base_reserves,
+        real_quote_reserves: pool_state.real_quote_reserves,
+        price: pool_state.current_price()?,
+        graduation_progress: pool_state.graduation_progress(main_state)?,
+        market_cap_usd: pool_state.market_cap_usd(main_state.sol_price)?,
+        real_sol_threshold: pool_state.real_sol_threshold,
+        complete: pool_state.complete,
+    })
+}
+
+impl<'info> AQuote<'info> {
+    // Trader's state, or a fresh one for wallets that never traded
+    fn user_state(&self) -> UserState {
+        match &self.user_state {
+            Some(user_state) => (***user_state).clone(),
+            None => UserState::default(),
+        }
+    }
+
+    fn fee_override(&self) -> Option<&FeeOverride> {
+        self.fee_override.as_deref().map(|fee_override| &**fee_override)
+    }
+
+    // Rewards the trader's referral chain would get, assuming it is passed to the trade
+    fn referral_reward(&self, fee: u64) -> Result<u64> {
+        let Some(user_state) = &self.user_state else {
+            return Ok(0);
+        };
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !self.main_state.referral_window_open(user_state, current_timestamp) {
+            return Ok(0);
+        }
+        let referred_volume = self
+            .referrer_state
+            .as_ref()
+            .filter(|referrer_state| referrer_state.referrer == user_state.referrer)
+            .map_or(0, |referrer_state| referrer_state.referred_volume);
+        let rates = self.main_state.referral_rates(referred_volume);
+        let mut reward: u64 = 0;
+        for (referrer, rate) in user_state.referral_chain().iter().zip(rates) {
+            if *referrer != Pubkey::default() {
+                reward = reward
+                    .checked_add(calculate_trading_fee(rate, fee)?)
+                    .ok_or(ThrustAppError::MathOverflow)?;
+            }
+        }
+        Ok(reward)
+    }
+}
+
+#[derive(Accounts)]
+pub struct AQuote<'info> {
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        seeds = [
+            PoolState::PREFIX_SEED,
+            pool_state.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// Trader's user state, used for referral rewards
+    pub user_state: Option<Box<Account<'info, UserState>>>,
+
+    /// Trader's token account, used for balance based sell taxes
+    #[account(token::mint = pool_state.mint)]
+    pub user_base_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Trader's holding record, used for decay taxes on hook tracked pools
+    #[account(constraint = holder_state.mint == pool_state.mint)]
+    pub holder_state: Option<Box<Account<'info, HolderState>>>,
+
+    /// Trader's direct referrer's vault, used for volume tiered referral rewards
+    pub referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Trader's negotiated trading fee, for wallets that have one
+    pub fee_override: Option<Box<Account<'info, FeeOverride>>>,
+}
@@ -2,34 +2,47 @@ use anchor_lang::solana_program::{hash::hash, secp256k1_recover::secp256k1_recov
 use anchor_lang::{prelude::*, system_program};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
 };
+use holding_hook::state::HolderState;
 use crate::{
-    constants::{FEE_PER_DIV, RESERVE_SEED},
+    constants::{CREATOR_VAULT_SEED, FEE_PER_DIV, RESERVE_SEED},
     error::ThrustAppError,
     main_state,
-    utils::{calculate_tax_rate, calculate_trading_fee, verify_signed_message},
-    MainState, PoolState, TradeEvent, UserState,
+    utils::{
+        calculate_trading_fee, calculate_volume_usd, transfer_tokens, verify_signed_message,
+    },
+    pay_referral_rewards, FeeOverride, MainState, PoolState, ReferralCode, ReferrerState,
+    TradeEvent, UserState,
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct SellInput {
     pub amount: u64,             // Amount of tokens to sell
+    pub min_sol_out: u64,        // Minimum amount of SOL to receive after fees and tax
     pub signature: [u8; 65],     // ECDSA signature of the message
-    pub last_received_time: u64,
+    pub last_received_time: u64, // Ignored, holding time comes from the holding hook's record
+    pub integrator_fee: u64,     // Fee rate paid to the integrator account, 0 for none
 }
 
-pub fn sell(ctx: Context<ASell>, input: SellInput) -> Result<()> {
-    let main_state = &mut ctx.accounts.main_state;
+pub fn sell<'info>(ctx: Context<'_, '_, '_, 'info, ASell<'info>>, input: SellInput) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
     let pool_state = &mut ctx.accounts.pool_state;
     let reserve_pda = &mut ctx.accounts.reserve_pda;
     let user_state = &mut ctx.accounts.user_state;
     let current_timestamp = Clock::get()?.unix_timestamp;
 
     verify_signed_message(&input.signature, &main_state.verify_signer_pubkey);
+    main_state.validate_integrator_fee(input.integrator_fee)?;
+    require!(
+        input.integrator_fee == 0 || ctx.accounts.integrator.is_some(),
+        ThrustAppError::IntegratorRequired
+    );
 
-    // Verify the signed message
-    let last_received_time = input.last_received_time;
+    let last_received_time = pool_state.last_received_time(
+        ctx.accounts.holder_state.as_deref().map(|holder| &**holder),
+        current_timestamp as u64,
+    )?;
 
     require!(
         main_state.initialized.eq(&true),
@@ -45,95 +58,120 @@ pub fn sell(ctx: Context<ASell>, input: SellInput) -> Result<()> {
     );
 
     let input_amount = input.amount;
-    let _output_amount = pool_state.compute_receivable_amount_on_sell(input_amount);
+    let _output_amount = pool_state.compute_receivable_amount_on_sell(input_amount)?;
 
     let current_timestamp = Clock::get()?.unix_timestamp as u64;
 
-    let fee_rate;
-    if pool_state.is_tax_active(current_timestamp) {
-        let seller_balance = ctx.accounts.seller_base_ata.amount;
-
-        fee_rate = calculate_tax_rate(
-            &pool_state.tax_type,
-            &user_state,
-            main_state.total_token_supply,
-            _output_amount,
-            current_timestamp,
-            seller_balance,
-            main_state.trading_fee,
-            last_received_time,
-        );
-    } else {
-        fee_rate = main_state.trading_fee;
-    }
+    let fee_override = ctx.accounts.fee_override.as_deref().map(|fee_override| &**fee_override);
+    let fee_rate = pool_state.sell_fee_rate(
+        main_state,
+        user_state,
+        fee_override,
+        _output_amount,
+        ctx.accounts.seller_base_ata.amount,
+        current_timestamp,
+        last_received_time,
+    )?;
 
-    let fee = calculate_trading_fee(fee_rate, _output_amount);
-    let output_amount = _output_amount - fee;
-    let mut referral_reward = 0;
+    let fee = calculate_trading_fee(fee_rate, _output_amount)?;
+    let integrator_fee = calculate_trading_fee(input.integrator_fee, _output_amount)?;
+    let output_amount = _output_amount
+        .checked_sub(fee)
+        .and_then(|amount| amount.checked_sub(integrator_fee))
+        .ok_or(ThrustAppError::MathOverflow)?;
+    require!(
+        output_amount >= input.min_sol_out,
+        ThrustAppError::SlippageExceeded
+    );
 
-    const STALENESS_THRESHOLD: u64 = 60; // staleness threshold in seconds
     let sol_price = main_state.sol_price;
+    // Tier the fee was charged at, before this trade adds to the volume
+    let (_, fee_tier) = pool_state.user_trading_fee(main_state, user_state, fee_override);
 
-    let trading_volume_usd = _output_amount * sol_price / 1_000_000_000;
-    user_state.trade_count += 1;
-    user_state.trading_volume_sol += _output_amount;
-    user_state.trading_volume_usd += trading_volume_usd;
+    let trading_volume_usd = calculate_volume_usd(_output_amount, sol_price)?;
+    user_state.record_trade(_output_amount, trading_volume_usd)?;
 
     msg!("Trading volume in USD: {}", trading_volume_usd);
 
-    // Store referrer to user state, only 1 time store.
-    if user_state.referrer == Pubkey::default() && ctx.accounts.referrer.key() != Pubkey::default()
-    {
-        user_state.referrer = ctx.accounts.referrer.key();
+    let mint_key = pool_state.mint;
+    let pool_signer_seeds: &[&[u8]] = &[
+        RESERVE_SEED,
+        mint_key.as_ref(),
+        &[ctx.bumps.reserve_pda],
+    ];
+    let pool_signer: &[&[&[u8]]] = &[pool_signer_seeds];
+
+    if let Some(referrer_state) = ctx.accounts.referrer_state.as_deref_mut() {
+        referrer_state.bind(
+            user_state,
+            &ctx.accounts.seller.key(),
+            ctx.accounts.referrer_user_state.as_deref(),
+        )?;
     }
+    // The fee was held back from the proceeds, so the referral cut leaves the reserve
+    let referral_reward = pay_referral_rewards(
+        main_state,
+        user_state,
+        [
+            ctx.accounts.referrer_state.as_deref_mut(),
+            ctx.accounts.level2_referrer_state.as_deref_mut(),
+            ctx.accounts.level3_referrer_state.as_deref_mut(),
+        ],
+        &reserve_pda.to_account_info(),
+        pool_signer,
+        &ctx.accounts.system_program.to_account_info(),
+        _output_amount,
+        fee,
+    )?;
 
-    if user_state.referrer != Pubkey::default()
-        && user_state.referrer == ctx.accounts.referrer.key()
-        && user_state.refer_trade_num <= main_state.referral_trade_limit
-    {
-        referral_reward = calculate_trading_fee(main_state.referral_reward_fee, fee);
+    let creator_fee = pool_state.accrue_creator_fee(fee)?;
+    // The protocol fee stays in the reserve, see `sweep_protocol_fees`
+    pool_state.accrue_protocol_fee(
+        fee.checked_sub(referral_reward)
+            .and_then(|fee| fee.checked_sub(creator_fee))
+            .ok_or(ThrustAppError::MathOverflow)?,
+    )?;
 
-        let referral_transfer_ctx = CpiContext::new(
+    if creator_fee > 0 {
+        let creator_fee_transfer_ctx = CpiContext::new_with_signer(
             ctx.accounts.system_program.to_account_info(),
             system_program::Transfer {
-                from: ctx.accounts.seller.to_account_info(),
-                to: ctx.accounts.referrer.to_account_info(),
+                from: reserve_pda.to_account_info(),
+                to: ctx.accounts.creator_vault.to_account_info(),
             },
+            pool_signer,
         );
-        system_program::transfer(referral_transfer_ctx, referral_reward)?;
-        user_state.refer_trade_num += 1;
+        system_program::transfer(creator_fee_transfer_ctx, creator_fee)?;
     }
 
-    let pool_signer_seeds: &[&[u8]] = &[
-        RESERVE_SEED,
-        pool_state.mint.as_ref(),
-        &[ctx.bumps.reserve_pda],
-    ];
-    let pool_signer: &[&[&[u8]]] = &[pool_signer_seeds];
+    if let Some(integrator) = ctx.accounts.integrator.as_ref().filter(|_| integrator_fee > 0) {
+        let integrator_fee_transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: reserve_pda.to_account_info(),
+                to: integrator.to_account_info(),
+            },
+            pool_signer,
+        );
+        system_program::transfer(integrator_fee_transfer_ctx, integrator_fee)?;
+    }
 
-    // Sending Fee in SOL from pool to fee address
-    let fee_transfer_ctx = CpiContext::new_with_signer(
-        ctx.accounts.system_program.to_account_info(),
-        system_program::Transfer {
-            from: reserve_pda.to_account_info(),
-            to: ctx.accounts.fee_recipient.to_account_info(),
-        },
-        pool_signer,
-    );
-    system_program::transfer(fee_transfer_ctx, fee - referral_reward)?;
 
     // Transfer Mint tokens from seller to pool
-    let token_transfer_cpi_account = Transfer {
+    let token_transfer_cpi_account = TransferChecked {
         from: ctx.accounts.seller_base_ata.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
         to: ctx.accounts.reserver_base_ata.to_account_info(),
         authority: ctx.accounts.seller.to_account_info(),
     };
-    token::transfer(
+    transfer_tokens(
         CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             token_transfer_cpi_account,
-        ),
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
         input_amount,
+        ctx.accounts.mint.decimals,
     )?;
 
     // Transfer SOL from pool to seller
@@ -152,8 +190,14 @@ pub fn sell(ctx: Context<ASell>, input: SellInput) -> Result<()> {
         mint: pool_state.mint,
         token_amount: input_amount,
         sol_amount: output_amount,
-        base_reserves: pool_state.real_base_reserves + pool_state.virt_base_reserves,
-        quote_reserves: pool_state.virt_quote_reserves + pool_state.real_quote_reserves,
+        fee,
+        creator_fee,
+        fee_tier,
+        integrator: ctx.accounts.integrator.as_ref().map(|integrator| integrator.key()),
+        integrator_fee,
+        unfilled_sol_amount: 0,
+        base_reserves: pool_state.base_reserves()?,
+        quote_reserves: pool_state.quote_reserves()?,
         is_buy: false,
         timestamp: Clock::get()?.unix_timestamp,
     });
@@ -166,16 +210,11 @@ pub struct ASell<'info> {
     #[account(mut)]
     pub seller: Signer<'info>,
     #[account(
-        mut,
         seeds = [MainState::PREFIX_SEED],
         bump,
     )]
     pub main_state: Box<Account<'info, MainState>>,
 
-    /// CHECK: This address is fee recipient address
-    #[account(mut, address = main_state.fee_recipient,)]
-    pub fee_recipient: AccountInfo<'info>,
-
     #[account(
         init_if_needed,
         payer = seller,
@@ -188,8 +227,55 @@ pub struct ASell<'info> {
     )]
     pub user_state: Box<Account<'info, UserState>>,
 
-    /// CHECK: Ensure referrer is valid address
-    pub referrer: AccountInfo<'info>,
+    /// Referrer's reward vault, for trades made through a referral
+    #[account(
+        mut,
+        seeds = [
+            ReferrerState::PREFIX_SEED,
+            referral_code
+                .as_ref()
+                .map_or(referrer_state.referrer, |referral_code| referral_code.referrer)
+                .as_ref(),
+        ],
+        bump,
+    )]
+    pub referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Reward vault of the referrer's referrer, for multi-level referral rewards
+    #[account(
+        mut,
+        seeds = [ReferrerState::PREFIX_SEED, level2_referrer_state.referrer.as_ref()],
+        bump,
+    )]
+    pub level2_referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Reward vault of the third referral level
+    #[account(
+        mut,
+        seeds = [ReferrerState::PREFIX_SEED, level3_referrer_state.referrer.as_ref()],
+        bump,
+    )]
+    pub level3_referrer_state: Option<Box<Account<'info, ReferrerState>>>,
+
+    /// Referrer's own user state, needed when the referrer gets bound to the seller
+    pub referrer_user_state: Option<Box<Account<'info, UserState>>>,
+
+    /// Referral code the trade came through, `referrer_state` is derived from its referrer
+    #[account(
+        constraint = referrer_state.is_some() @ ThrustAppError::ReferrerStateRequired,
+    )]
+    pub referral_code: Option<Box<Account<'info, ReferralCode>>>,
+
+    /// Seller's negotiated trading fee, for wallets that have one
+    #[account(
+        seeds = [FeeOverride::PREFIX_SEED, seller.key().as_ref()],
+        bump,
+    )]
+    pub fee_override: Option<Box<Account<'info, FeeOverride>>>,
+
+    /// CHECK: Frontend or bot receiving the integrator fee, any address
+    #[account(mut)]
+    pub integrator: Option<UncheckedAccount<'info>>,
 
     #[account(
         mut,
@@ -202,7 +288,7 @@ pub struct ASell<'info> {
     pub pool_state: Box<Account<'info, PoolState>>,
 
     #[account(address = pool_state.mint)]
-    pub mint: Box<Account<'info, Mint>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
@@ -214,22 +300,42 @@ pub struct ASell<'info> {
     )]
     pub reserve_pda: SystemAccount<'info>,
 
+    #[account(
+        mut,
+        seeds = [
+            CREATOR_VAULT_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub creator_vault: SystemAccount<'info>,
+
     #[account(
         init_if_needed,
         payer = seller,
         associated_token::mint = mint,
+        associated_token::token_program = token_program,
         associated_token::authority = seller,
     )]
-    pub seller_base_ata: Box<Account<'info, TokenAccount>>,
+    pub seller_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = seller,
         associated_token::mint = mint,
+        associated_token::token_program = token_program,
         associated_token::authority = pool_state,
     )]
-    pub reserver_base_ata: Box<Account<'info, TokenAccount>>,
+    pub reserver_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Seller's holding record, required for pools launched with the holding hook
+    #[account(
+        seeds = [HolderState::PREFIX_SEED, seller_base_ata.key().as_ref()],
+        seeds::program = holding_hook::ID,
+        bump,
+    )]
+    pub holder_state: Option<Box<Account<'info, HolderState>>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
@@ -0,0 +1,97 @@
+use anchor_lang::{prelude::*, system_program};
+use crate::{
+    constants::RESERVE_SEED, error::ThrustAppError, FeeVault, MainState, PoolState,
+    SweepProtocolFeesEvent,
+};
+
+/// Moves the protocol fees a pool accrued in its reserve to the fee vault, from where
+/// `distribute_fees` pays them to the fee split. Anyone can call it.
+pub fn sweep_protocol_fees(ctx: Context<ASweepProtocolFees>) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(
+        main_state.initialized.eq(&true),
+        ThrustAppError::Uninitialized
+    );
+
+    let amount = transfer_protocol_fees(
+        &mut ctx.accounts.pool_state,
+        ctx.accounts.reserve_pda.to_account_info(),
+        ctx.accounts.fee_vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.bumps.reserve_pda,
+    )?;
+    require!(amount > 0, ThrustAppError::NothingToClaim);
+    Ok(())
+}
+
+/// Sends the accrued protocol fees out of the reserve and resets them, returning the amount.
+pub fn transfer_protocol_fees<'info>(
+    pool_state: &mut PoolState,
+    reserve_pda: AccountInfo<'info>,
+    fee_vault: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    reserve_bump: u8,
+) -> Result<u64> {
+    let amount = pool_state.protocol_fees;
+    if amount == 0 {
+        return Ok(0);
+    }
+    pool_state.protocol_fees = 0;
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program,
+            system_program::Transfer {
+                from: reserve_pda,
+                to: fee_vault,
+            },
+            &[&[RESERVE_SEED, pool_state.mint.as_ref(), &[reserve_bump]]],
+        ),
+        amount,
+    )?;
+
+    emit!(SweepProtocolFeesEvent {
+        mint: pool_state.mint,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(amount)
+}
+
+#[derive(Accounts)]
+pub struct ASweepProtocolFees<'info> {
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        mut,
+        seeds = [FeeVault::PREFIX_SEED],
+        bump,
+    )]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PoolState::PREFIX_SEED,
+            pool_state.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        mut,
+        seeds = [
+            RESERVE_SEED,
+            pool_state.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub reserve_pda: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
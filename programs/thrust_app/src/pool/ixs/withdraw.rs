@@ -1,12 +1,15 @@
 use anchor_lang::{prelude::*, system_program};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use crate::{
+    constants::RESERVE_SEED, error::ThrustAppError, transfer_protocol_fees, utils::transfer_tokens,
+    FeeVault, MainState, PoolState,
 };
-use crate::{constants::RESERVE_SEED, error::ThrustAppError, MainState, PoolState};
 use std::str::FromStr;
 
-pub fn withdraw(ctx: Context<AWithdrawState>) -> Result<()> {
+pub fn withdraw<'info>(ctx: Context<'_, '_, '_, 'info, AWithdrawState<'info>>) -> Result<()> {
     let reserve_pda = &mut ctx.accounts.reserve_pda;
     let owner = ctx.accounts.owner.to_account_info();
     let main_state = &ctx.accounts.main_state;
@@ -24,18 +27,20 @@ pub fn withdraw(ctx: Context<AWithdrawState>) -> Result<()> {
         pool_state.withdrawn.eq(&false),
         ThrustAppError::AlreadyWithdrawn
     );
+    require!(pool_state.amm.is_none(), ThrustAppError::AmmActive);
 
     let owner_base_ata = ctx.accounts.owner_base_ata.to_account_info();
     let token_program = ctx.accounts.token_program.to_account_info();
 
     pool_state.withdrawn = true;
     // send tokens in pool and virt
-    let pool_base_transfer_cpi_account = Transfer {
+    let pool_base_transfer_cpi_account = TransferChecked {
         from: ctx.accounts.reserver_base_ata.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
         to: owner_base_ata.clone(),
         authority: pool_state.to_account_info(),
     };
-    token::transfer(
+    transfer_tokens(
         CpiContext::new_with_signer(
             token_program.clone(),
             pool_base_transfer_cpi_account,
@@ -44,8 +49,19 @@ pub fn withdraw(ctx: Context<AWithdrawState>) -> Result<()> {
                 pool_state.mint.as_ref(),
                 &[ctx.bumps.pool_state],
             ]],
-        ),
-        pool_state.virt_base_reserves + pool_state.real_base_reserves,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        pool_state.base_reserves()?,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    // Protocol fees still in the reserve go to the fee vault, not the owner
+    transfer_protocol_fees(
+        pool_state,
+        reserve_pda.to_account_info(),
+        ctx.accounts.fee_vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.bumps.reserve_pda,
     )?;
 
     // Get the current balance of the PDA
@@ -82,6 +98,13 @@ pub struct AWithdrawState<'info> {
     )]
     pub main_state: Box<Account<'info, MainState>>,
 
+    #[account(
+        mut,
+        seeds = [FeeVault::PREFIX_SEED],
+        bump,
+    )]
+    pub fee_vault: Box<Account<'info, FeeVault>>,
+
     #[account(
         mut,
         seeds = [
@@ -93,7 +116,7 @@ pub struct AWithdrawState<'info> {
     pub pool_state: Box<Account<'info, PoolState>>,
 
     #[account(mut,)]
-    pub mint: Box<Account<'info, Mint>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
@@ -109,19 +132,21 @@ pub struct AWithdrawState<'info> {
         init_if_needed,
         payer = owner,
         associated_token::mint = mint,
+        associated_token::token_program = token_program,
         associated_token::authority = pool_state,
     )]
-    pub reserver_base_ata: Box<Account<'info, TokenAccount>>,
+    pub reserver_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = owner,
         associated_token::mint = mint,
+        associated_token::token_program = token_program,
         associated_token::authority = owner,
     )]
-    pub owner_base_ata: Box<Account<'info, TokenAccount>>,
+    pub owner_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
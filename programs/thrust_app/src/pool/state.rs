@@ -1,5 +1,11 @@
 use anchor_lang::prelude::*;
-use crate::constants::REAL_SOL_THRESHOLD;
+use holding_hook::state::HolderState;
+use crate::{
+    constants::{CURVE_PRECISION, FEE_PER_DIV, PRICE_PRECISION},
+    error::ThrustAppError,
+    utils::{calculate_tax_rate, calculate_trading_fee, calculate_volume_usd},
+    CurveType, FeeOverride, MainState, PoolAmm, UserState,
+};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
 pub enum TaxDuration {
@@ -34,6 +40,18 @@ pub enum TaxType {
     },
 }
 
+impl TaxType {
+    /// Bit identifying the tax type in `LaunchProfile::allowed_tax_types`.
+    pub fn flag(&self) -> u8 {
+        match self {
+            TaxType::Disabled => 1 << 0,
+            TaxType::HigherSellTax { .. } => 1 << 1,
+            TaxType::DecayTax { .. } => 1 << 2,
+            TaxType::FixedTax { .. } => 1 << 3,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct WaitingRoomConfig {
     pub min_trades: u32,
@@ -56,6 +74,45 @@ pub enum WaitingRoomState {
     },
 }
 
+impl WaitingRoomState {
+    /// Counts a buy of `sol_amount` SOL towards the waiting room, as a new participant
+    /// when the buyer held none of the token yet, and closes the room once the buy meets
+    /// its participant or volume condition.
+    pub fn record_buy(&mut self, new_participant: bool, sol_amount: u64) -> Result<()> {
+        if let WaitingRoomState::Enabled {
+            closure_condition,
+            participants,
+            total_buy_volume,
+            closed,
+            ..
+        } = self
+        {
+            if new_participant {
+                *participants = participants
+                    .checked_add(1)
+                    .ok_or(ThrustAppError::MathOverflow)?;
+            }
+            *total_buy_volume = total_buy_volume
+                .checked_add(sol_amount)
+                .ok_or(ThrustAppError::MathOverflow)?;
+
+            // Auto-close if condition met
+            if !*closed {
+                match closure_condition {
+                    ClosureCondition::ParticipantCount { max_participants } if *participants >= *max_participants => {
+                        *closed = true;
+                    }
+                    ClosureCondition::BuyVolume { close_volume } if *total_buy_volume >= *close_volume => {
+                        *closed = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
 pub enum ClosureCondition {
     TimeBased {
@@ -69,6 +126,36 @@ pub enum ClosureCondition {
     },
 }
 
+/// When a pool completes. The pool's SOL threshold always caps the curve, so a
+/// market cap target graduates the pool at whichever of the two is hit first.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub enum GraduationMode {
+    RealSol,
+    MarketCapUsd {
+        target_usd: u64, // USD scaled like `UserState::trading_volume_usd`
+    },
+}
+
+/// Outcome of a buy applied to the curve.
+#[derive(Clone, Debug)]
+pub struct BuyFill {
+    pub input_amount: u64,    // SOL added to the curve
+    pub fee: u64,             // Trading fee charged on the filled part
+    pub integrator_fee: u64,  // Charged on top of the trading fee for the integrator
+    pub output_amount: u64,   // Tokens bought
+    pub unfilled_amount: u64, // Requested SOL not spent because the curve hit the threshold
+}
+
+impl BuyFill {
+    /// SOL charged to the buyer, fees included.
+    pub fn sol_amount(&self) -> Result<u64> {
+        self.input_amount
+            .checked_add(self.fee)
+            .and_then(|amount| amount.checked_add(self.integrator_fee))
+            .ok_or(ThrustAppError::MathOverflow.into())
+    }
+}
+
 #[account]
 pub struct PoolState {
     pub owner: Pubkey,
@@ -84,59 +171,418 @@ pub struct PoolState {
     pub tax_type: TaxType,
     pub tax_start_timestamp: u64,
     pub waiting_room_state: WaitingRoomState,
+    pub curve_type: CurveType,
+    pub init_real_base_reserves: u64,
+    pub real_sol_threshold: u64, // SOL raised at which the pool graduates
+    pub graduate_fee: u64,       // Paid to the fee recipient on graduation
+    pub graduation_mode: GraduationMode,
+    pub trading_fee: Option<u64>, // Overrides the main state trading fee, set from a launch profile
+    pub total_supply: u64,        // Minted supply of the pool's token
+    pub decimals: u8,
+    pub holding_hook: bool, // Holding time is recorded by the holding_hook transfer hook
+    pub creator_fee_share: u64,    // Part of each trading fee paid to the creator vault
+    pub creator_fees_accrued: u64, // Total paid to the creator vault, claimed or not
+    pub protocol_fees: u64,        // Held in the reserve until swept to the fee recipient
+    pub amm_pool: Option<Pubkey>,  // AMM pool the liquidity was migrated to
+    pub amm: Option<PoolAmm>,      // Built-in AMM the pool trades on after graduating
+    pub graduation_bounty: u64,    // Held back from the graduation fee for the migrating keeper
 }
 
 impl PoolState {
     pub const MAX_SIZE: usize = std::mem::size_of::<Self>() + 100;
     pub const PREFIX_SEED: &'static [u8] = b"pool";
 
-    pub fn is_tax_active(&self, current_timestamp: u64) -> bool {
-        match &self.tax_type {
+    /// When the seller last received tokens, read from the holding hook's record. Nothing
+    /// vouches for the holding time of pools without the hook, so their sellers get no
+    /// credit for it.
+    pub fn last_received_time(
+        &self,
+        holder_state: Option<&HolderState>,
+        current_timestamp: u64,
+    ) -> Result<u64> {
+        if !self.holding_hook {
+            return Ok(current_timestamp);
+        }
+        let holder_state = holder_state.ok_or(ThrustAppError::HolderStateRequired)?;
+        Ok(holder_state.last_received_time)
+    }
+
+    pub fn is_tax_active(&self, current_timestamp: u64) -> Result<bool> {
+        let active = match &self.tax_type {
             TaxType::Disabled => false,
             TaxType::HigherSellTax { duration, .. }
             | TaxType::DecayTax { duration, .. }
             | TaxType::FixedTax { duration, .. } => match duration {
                 TaxDuration::Lifetime => true,
                 TaxDuration::FixedDuration(days) => {
-                    let elapsed_days = (current_timestamp - self.tax_start_timestamp) / 86400;
+                    let elapsed_days = current_timestamp
+                        .checked_sub(self.tax_start_timestamp)
+                        .ok_or(ThrustAppError::MathOverflow)?
+                        / 86400;
                     elapsed_days <= *days
                 }
             },
+        };
+        Ok(active)
+    }
+
+    /// Applies a buy of `amount` SOL (fees included) to the curve. The fees are charged on
+    /// the filled input, so `amount` is split into the input and the fees on it. When the
+    /// buy crosses the graduation threshold only the remaining capacity is filled and the
+    /// rest of `amount` is reported as unfilled.
+    pub fn apply_buy(
+        &mut self,
+        amount: u64,
+        trading_fee: u64,
+        integrator_fee: u64,
+    ) -> Result<BuyFill> {
+        let fee_base = FEE_PER_DIV * 100;
+        let affordable = (amount as u128)
+            .checked_mul(fee_base)
+            .ok_or(ThrustAppError::MathOverflow)?
+            / (fee_base + trading_fee as u128 + integrator_fee as u128);
+        let remaining = self.remaining_to_threshold();
+        let filled = u64::try_from(affordable)
+            .map_err(|_| ThrustAppError::MathOverflow)?
+            .min(remaining);
+        let fee = calculate_trading_fee(trading_fee, filled)?;
+        let integrator_amount = calculate_trading_fee(integrator_fee, filled)?;
+        // The rounding left over goes into the curve too, up to its remaining capacity
+        let input_amount = amount
+            .checked_sub(fee)
+            .and_then(|amount| amount.checked_sub(integrator_amount))
+            .ok_or(ThrustAppError::MathOverflow)?
+            .min(remaining);
+        let output_amount = self.compute_receivable_amount_on_buy(input_amount)?;
+
+        let mut fill = BuyFill {
+            input_amount,
+            fee,
+            integrator_fee: integrator_amount,
+            output_amount,
+            unfilled_amount: 0,
+        };
+        fill.unfilled_amount = amount
+            .checked_sub(fill.sol_amount()?)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        Ok(fill)
+    }
+
+    /// Fee rate charged on a sell, the pool's sell tax while it is active,
+    /// otherwise the seller's trading fee.
+    #[allow(clippy::too_many_arguments)]
+    pub fn sell_fee_rate(
+        &self,
+        main_state: &MainState,
+        user_state: &UserState,
+        fee_override: Option<&FeeOverride>,
+        sell_amount: u64,
+        seller_balance: u64,
+        current_timestamp: u64,
+        last_received_time: u64,
+    ) -> Result<u64> {
+        let trading_fee = self.user_trading_fee(main_state, user_state, fee_override).0;
+        let tax_exempt = matches!(fee_override, Some(fee_override) if fee_override.tax_exempt);
+        if self.is_tax_active(current_timestamp)? && !tax_exempt {
+            calculate_tax_rate(
+                &self.tax_type,
+                user_state,
+                self.total_supply,
+                sell_amount,
+                current_timestamp,
+                seller_balance,
+                trading_fee,
+                last_received_time,
+            )
+        } else {
+            Ok(trading_fee)
+        }
+    }
+
+    pub fn trading_fee(&self, main_state: &MainState) -> u64 {
+        self.trading_fee.unwrap_or(main_state.trading_fee)
+    }
+
+    /// Trading fee for a user, with the volume tier when it lowered the pool's fee.
+    /// A wallet's fee override replaces both.
+    pub fn user_trading_fee(
+        &self,
+        main_state: &MainState,
+        user_state: &UserState,
+        fee_override: Option<&FeeOverride>,
+    ) -> (u64, Option<u8>) {
+        if let Some(fee_override) = fee_override {
+            return (fee_override.trading_fee, None);
+        }
+        let trading_fee = self.trading_fee(main_state);
+        match main_state.fee_tier(user_state) {
+            Some((tier, tier_fee)) if tier_fee < trading_fee => (tier_fee, Some(tier)),
+            _ => (trading_fee, None),
+        }
+    }
+
+    /// Creator's cut of a charged fee, recorded as accrued to the creator vault.
+    pub fn accrue_creator_fee(&mut self, fee: u64) -> Result<u64> {
+        let creator_fee = calculate_trading_fee(self.creator_fee_share, fee)?;
+        self.creator_fees_accrued = self
+            .creator_fees_accrued
+            .checked_add(creator_fee)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        Ok(creator_fee)
+    }
+
+    /// Tokens worth `quote_amount` lamports at the curve's current price, capped at the
+    /// tokens left in the pool. Graduated liquidity is paired at this ratio so it opens
+    /// where the curve closed instead of below it.
+    pub fn base_at_closing_price(&self, quote_amount: u64) -> Result<u64> {
+        let (numerator, denominator) = match &self.curve_type {
+            CurveType::ConstantProduct => {
+                (self.base_reserves()? as u128, self.quote_reserves()? as u128)
+            }
+            curve => (CURVE_PRECISION, curve.price(self.tokens_sold()?)?),
+        };
+        let base_amount = (quote_amount as u128)
+            .checked_mul(numerator)
+            .and_then(|value| value.checked_div(denominator))
+            .ok_or(ThrustAppError::MathOverflow)?;
+        let base_reserves = self.base_reserves()?;
+        Ok(u64::try_from(base_amount).map_or(base_reserves, |amount| amount.min(base_reserves)))
+    }
+
+    /// Turns the graduated curve into the built-in AMM, seeded with the pool's real SOL
+    /// less the graduation fee and the tokens matching it at the closing price. The tokens
+    /// left over stay locked in the reserve, outside the AMM's reserves.
+    pub fn open_amm(&mut self, fee: u64) -> Result<()> {
+        let quote_reserve = self
+            .real_quote_reserves
+            .checked_sub(self.graduate_fee)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        let base_reserve = self.base_at_closing_price(quote_reserve)?;
+        self.amm = Some(PoolAmm::new(base_reserve, quote_reserve, fee)?);
+        Ok(())
+    }
+
+    /// Protocol fees stay in the reserve, apart from the curve's SOL, until they are swept.
+    pub fn accrue_protocol_fee(&mut self, amount: u64) -> Result<()> {
+        self.protocol_fees = self
+            .protocol_fees
+            .checked_add(amount)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Virtual plus real token reserves.
+    pub fn base_reserves(&self) -> Result<u64> {
+        self.virt_base_reserves
+            .checked_add(self.real_base_reserves)
+            .ok_or(ThrustAppError::MathOverflow.into())
+    }
+
+    /// Virtual plus real SOL reserves.
+    pub fn quote_reserves(&self) -> Result<u64> {
+        self.virt_quote_reserves
+            .checked_add(self.real_quote_reserves)
+            .ok_or(ThrustAppError::MathOverflow.into())
+    }
+
+    /// Tokens bought out of the curve so far.
+    pub fn tokens_sold(&self) -> Result<u64> {
+        self.init_real_base_reserves
+            .checked_sub(self.real_base_reserves)
+            .ok_or(ThrustAppError::MathOverflow.into())
+    }
+
+    /// Spot price in lamports per base unit, scaled by `PRICE_PRECISION`.
+    pub fn current_price(&self) -> Result<u64> {
+        let price = match &self.curve_type {
+            CurveType::ConstantProduct => (self.quote_reserves()? as u128)
+                .checked_mul(PRICE_PRECISION)
+                .ok_or(ThrustAppError::MathOverflow)?
+                .checked_div(self.base_reserves()? as u128)
+                .ok_or(ThrustAppError::MathOverflow)?,
+            curve => curve.price(self.tokens_sold()?)? / (CURVE_PRECISION / PRICE_PRECISION),
+        };
+        u64::try_from(price).map_err(|_| ThrustAppError::MathOverflow.into())
+    }
+
+    /// SOL still accepted before the pool reaches its graduation threshold.
+    pub fn remaining_to_threshold(&self) -> u64 {
+        self.real_sol_threshold.saturating_sub(self.real_quote_reserves)
+    }
+
+    /// Fully diluted market cap at the current price, in USD scaled like `calculate_volume_usd`.
+    pub fn market_cap_usd(&self, sol_price: u64) -> Result<u64> {
+        let market_cap = match &self.curve_type {
+            CurveType::ConstantProduct => (self.quote_reserves()? as u128)
+                .checked_mul(self.total_supply as u128)
+                .ok_or(ThrustAppError::MathOverflow)?
+                .checked_div(self.base_reserves()? as u128)
+                .ok_or(ThrustAppError::MathOverflow)?,
+            curve => curve
+                .price(self.tokens_sold()?)?
+                .checked_mul(self.total_supply as u128)
+                .ok_or(ThrustAppError::MathOverflow)?
+                / CURVE_PRECISION,
+        };
+        let market_cap = u64::try_from(market_cap).map_err(|_| ThrustAppError::MathOverflow)?;
+        calculate_volume_usd(market_cap, sol_price)
+    }
+
+    /// Share of the way to graduation, where 100_000 is 100%.
+    pub fn graduation_progress(&self, main_state: &MainState) -> Result<u64> {
+        let full = FEE_PER_DIV * 100;
+        let mut progress =
+            (self.real_quote_reserves as u128) * full / (self.real_sol_threshold as u128);
+        if let GraduationMode::MarketCapUsd { target_usd } = self.graduation_mode {
+            let market_cap = self.market_cap_usd(main_state.sol_price)?;
+            progress = progress.max((market_cap as u128) * full / (target_usd as u128));
+        }
+        Ok(progress.min(full) as u64)
+    }
+
+    pub fn is_graduated(&self, main_state: &MainState) -> Result<bool> {
+        if self.real_quote_reserves >= self.real_sol_threshold {
+            return Ok(true);
         }
+        let graduated = match self.graduation_mode {
+            GraduationMode::RealSol => false,
+            GraduationMode::MarketCapUsd { target_usd } => {
+                // The reserve must hold enough to pay the graduation fee
+                self.real_quote_reserves >= self.graduate_fee
+                    && self.market_cap_usd(main_state.sol_price)? >= target_usd
+            }
+        };
+        Ok(graduated)
     }
 
-    pub fn compute_receivable_amount_on_buy(&mut self, quote_amount: u64) -> u64 {
+    pub fn compute_receivable_amount_on_buy(&mut self, quote_amount: u64) -> Result<u64> {
         let mut amount = quote_amount;
-        if (amount + self.real_quote_reserves > REAL_SOL_THRESHOLD) {
-            amount = REAL_SOL_THRESHOLD - self.real_quote_reserves;
+        let remaining = self.remaining_to_threshold();
+        if amount > remaining {
+            amount = remaining;
         }
-        let base_amount = calculate_output_amount(
-            amount,
-            self.virt_quote_reserves + self.real_quote_reserves,
-            self.real_base_reserves + self.virt_base_reserves,
+        let base_amount = match &self.curve_type {
+            CurveType::ConstantProduct => calculate_output_amount(
+                amount,
+                self.quote_reserves()?,
+                self.base_reserves()?,
+            )?,
+            curve => curve.base_out(self.tokens_sold()?, amount)?,
+        };
+        require!(base_amount > 0, ThrustAppError::ZeroOutput);
+        require!(
+            base_amount <= self.real_base_reserves,
+            ThrustAppError::InsufficientLiquidity
+        );
+
+        let product_before = self.virtual_product()?;
+        self.real_base_reserves -= base_amount;
+        self.real_quote_reserves = self
+            .real_quote_reserves
+            .checked_add(amount)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        self.check_invariant(product_before)?;
+        Ok(base_amount)
+    }
+
+    pub fn compute_payable_amount_on_buy(&mut self, base_amount: u64) -> Result<u64> {
+        require!(base_amount > 0, ThrustAppError::ZeroOutput);
+        require!(
+            base_amount <= self.real_base_reserves,
+            ThrustAppError::InsufficientLiquidity
         );
+        let quote_amount = match &self.curve_type {
+            CurveType::ConstantProduct => calculate_input_amount(
+                base_amount,
+                self.quote_reserves()?,
+                self.base_reserves()?,
+            )?,
+            curve => curve.quote_in(self.tokens_sold()?, base_amount)?,
+        };
+
+        let product_before = self.virtual_product()?;
         self.real_base_reserves -= base_amount;
-        self.real_quote_reserves += amount;
-        base_amount
+        self.real_quote_reserves = self
+            .real_quote_reserves
+            .checked_add(quote_amount)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        self.check_invariant(product_before)?;
+        Ok(quote_amount)
     }
 
-    pub fn compute_receivable_amount_on_sell(&mut self, base_amount: u64) -> u64 {
-        let quote_amount = calculate_output_amount(
-            base_amount,
-            self.real_base_reserves + self.virt_base_reserves,
-            self.virt_quote_reserves + self.real_quote_reserves,
+    pub fn compute_receivable_amount_on_sell(&mut self, base_amount: u64) -> Result<u64> {
+        let quote_amount = match &self.curve_type {
+            CurveType::ConstantProduct => calculate_output_amount(
+                base_amount,
+                self.base_reserves()?,
+                self.quote_reserves()?,
+            )?,
+            curve => curve.quote_out(self.tokens_sold()?, base_amount)?,
+        };
+        require!(quote_amount > 0, ThrustAppError::ZeroOutput);
+        require!(
+            quote_amount <= self.real_quote_reserves,
+            ThrustAppError::InsufficientLiquidity
         );
-        self.real_base_reserves += base_amount;
+
+        let product_before = self.virtual_product()?;
+        self.real_base_reserves = self
+            .real_base_reserves
+            .checked_add(base_amount)
+            .ok_or(ThrustAppError::MathOverflow)?;
         self.real_quote_reserves -= quote_amount;
-        quote_amount
+        self.check_invariant(product_before)?;
+        Ok(quote_amount)
+    }
+
+    fn virtual_product(&self) -> Result<u128> {
+        Ok((self.quote_reserves()? as u128) * (self.base_reserves()? as u128))
+    }
+
+    // Constant product pools must never lose value on the virtual product, other curves
+    // must always hold enough SOL to buy back every token sold.
+    fn check_invariant(&self, product_before: u128) -> Result<()> {
+        let holds = match &self.curve_type {
+            CurveType::ConstantProduct => self.virtual_product()? >= product_before,
+            curve => {
+                let sold = self.tokens_sold()?;
+                self.real_quote_reserves >= curve.quote_out(sold, sold)?
+            }
+        };
+        require!(holds, ThrustAppError::InvariantViolation);
+        Ok(())
     }
 }
 
-fn calculate_output_amount(input_amount: u64, input_reserve: u64, output_reserve: u64) -> u64 {
+pub(crate) fn calculate_output_amount(
+    input_amount: u64,
+    input_reserve: u64,
+    output_reserve: u64,
+) -> Result<u64> {
     let output_amount = (output_reserve as u128)
         .checked_mul(input_amount as u128)
-        .unwrap()
+        .ok_or(ThrustAppError::MathOverflow)?
         .checked_div((input_reserve as u128) + (input_amount as u128))
-        .unwrap();
-    output_amount as u64
+        .ok_or(ThrustAppError::MathOverflow)?;
+    u64::try_from(output_amount).map_err(|_| ThrustAppError::MathOverflow.into())
+}
+
+// Inverse of `calculate_output_amount`, rounded up so the pool never gives away value.
+fn calculate_input_amount(
+    output_amount: u64,
+    input_reserve: u64,
+    output_reserve: u64,
+) -> Result<u64> {
+    let numerator = (input_reserve as u128)
+        .checked_mul(output_amount as u128)
+        .ok_or(ThrustAppError::MathOverflow)?;
+    let denominator = (output_reserve as u128)
+        .checked_sub(output_amount as u128)
+        .filter(|denominator| *denominator > 0)
+        .ok_or(ThrustAppError::InsufficientLiquidity)?;
+    let input_amount = numerator
+        .checked_add(denominator - 1)
+        .ok_or(ThrustAppError::MathOverflow)?
+        / denominator;
+    u64::try_from(input_amount).map_err(|_| ThrustAppError::MathOverflow.into())
 }
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::{error::ThrustAppError, ClaimReferralRewardsEvent, ReferrerState};
+
+/// Pays the referrer the rewards accrued in their vault, keeping it rent exempt.
+pub fn claim_referral_rewards(ctx: Context<AClaimReferralRewards>) -> Result<()> {
+    let referrer_state = &mut ctx.accounts.referrer_state;
+    let rent_exempt = Rent::get()?.minimum_balance(referrer_state.to_account_info().data_len());
+    let amount = referrer_state.get_lamports().saturating_sub(rent_exempt);
+    require!(amount > 0, ThrustAppError::NothingToClaim);
+
+    // The vault is owned by the program, so lamports move without a system transfer
+    referrer_state.sub_lamports(amount)?;
+    ctx.accounts.referrer.add_lamports(amount)?;
+    referrer_state.total_claimed = referrer_state
+        .total_claimed
+        .checked_add(amount)
+        .ok_or(ThrustAppError::MathOverflow)?;
+
+    emit!(ClaimReferralRewardsEvent {
+        referrer: referrer_state.referrer,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AClaimReferralRewards<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ReferrerState::PREFIX_SEED, referrer.key().as_ref()],
+        bump,
+        has_one = referrer @ ThrustAppError::Unauthorised,
+    )]
+    pub referrer_state: Box<Account<'info, ReferrerState>>,
+}
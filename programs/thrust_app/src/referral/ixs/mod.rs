@@ -0,0 +1,8 @@
+pub mod register_referrer;
+pub use register_referrer::*;
+
+pub mod claim_referral_rewards;
+pub use claim_referral_rewards::*;
+
+pub mod register_referral_code;
+pub use register_referral_code::*;
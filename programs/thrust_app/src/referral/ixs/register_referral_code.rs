@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::{error::ThrustAppError, ReferralCode, ReferrerState};
+
+/// Claims a referral code for a registered referrer. Codes are first come, first served.
+pub fn register_referral_code(ctx: Context<ARegisterReferralCode>, code: [u8; 16]) -> Result<()> {
+    ReferralCode::validate_code(&code)?;
+
+    let referral_code = &mut ctx.accounts.referral_code;
+    referral_code.code = code;
+    referral_code.referrer = ctx.accounts.referrer.key();
+
+    msg!("Registered referral code for {}", referral_code.referrer);
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(code: [u8; 16])]
+pub struct ARegisterReferralCode<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    #[account(
+        seeds = [ReferrerState::PREFIX_SEED, referrer.key().as_ref()],
+        bump,
+        has_one = referrer @ ThrustAppError::Unauthorised,
+    )]
+    pub referrer_state: Box<Account<'info, ReferrerState>>,
+
+    #[account(
+        init,
+        payer = referrer,
+        space = 8 + ReferralCode::MAX_SIZE,
+        seeds = [ReferralCode::PREFIX_SEED, code.as_ref()],
+        bump,
+    )]
+    pub referral_code: Box<Account<'info, ReferralCode>>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::{ReferrerState, UserState};
+
+/// Opens the referrer's reward vault. Traders can only be referred by registered referrers.
+pub fn register_referrer(ctx: Context<ARegisterReferrer>) -> Result<()> {
+    let referrer_state = &mut ctx.accounts.referrer_state;
+    referrer_state.referrer = ctx.accounts.referrer.key();
+
+    msg!("Registered referrer {}", referrer_state.referrer);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ARegisterReferrer<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = referrer,
+        space = 8 + ReferrerState::MAX_SIZE,
+        seeds = [ReferrerState::PREFIX_SEED, referrer.key().as_ref()],
+        bump,
+    )]
+    pub referrer_state: Box<Account<'info, ReferrerState>>,
+
+    /// Created so referral cycles can be checked against it
+    #[account(
+        init_if_needed,
+        payer = referrer,
+        space = 8 + UserState::MAX_SIZE,
+        seeds = [UserState::PREFIX_SEED, referrer.key().as_ref()],
+        bump,
+    )]
+    pub user_state: Box<Account<'info, UserState>>,
+    pub system_program: Program<'info, System>,
+}
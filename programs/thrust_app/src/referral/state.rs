@@ -0,0 +1,176 @@
+use anchor_lang::{prelude::*, system_program};
+use crate::{error::ThrustAppError, utils::calculate_trading_fee, MainState, UserState};
+
+/// A referrer's reward vault and stats. Rewards are paid into this account by the
+/// referred traders and claimed with `claim_referral_rewards`.
+#[account]
+#[derive(Default)]
+pub struct ReferrerState {
+    pub referrer: Pubkey,
+    pub referees: u64,        // Users who traded with this referrer first
+    pub referred_volume: u64, // SOL volume of the referees' trades
+    pub total_earned: u64,
+    pub total_claimed: u64,
+}
+
+impl ReferrerState {
+    pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
+    pub const PREFIX_SEED: &'static [u8] = b"referrer";
+    pub const LEVELS: usize = 3;
+
+    /// Makes this referrer the trader's referrer, unless the trader already has one.
+    /// `referrer_user_state` is the referrer's own user state; its referrers become the
+    /// trader's upline, checked so the trader never ends up in their own chain.
+    pub fn bind(
+        &mut self,
+        user_state: &mut UserState,
+        trader: &Pubkey,
+        referrer_user_state: Option<&Account<UserState>>,
+    ) -> Result<()> {
+        if user_state.referrer != Pubkey::default() {
+            return Ok(());
+        }
+        require_keys_neq!(self.referrer, *trader, ThrustAppError::SelfReferral);
+
+        let referrer_user_state =
+            referrer_user_state.ok_or(ThrustAppError::ReferrerUserStateRequired)?;
+        let (expected_key, _) = Pubkey::find_program_address(
+            &[UserState::PREFIX_SEED, self.referrer.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            referrer_user_state.key(),
+            expected_key,
+            ThrustAppError::ReferrerUserStateRequired
+        );
+        require!(
+            referrer_user_state.referrer != *trader && referrer_user_state.upline[0] != *trader,
+            ThrustAppError::ReferralCycle
+        );
+
+        user_state.referrer = self.referrer;
+        user_state.upline = [referrer_user_state.referrer, referrer_user_state.upline[0]];
+        user_state.referred_at = Clock::get()?.unix_timestamp as u64;
+        self.referees = self
+            .referees
+            .checked_add(1)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Records a referee's trade and returns the reward owed out of its `fee`, where
+    /// `level` is this referrer's place in the trader's chain, 0 for the direct referrer.
+    /// Referrers at another place in the chain earn nothing.
+    pub fn accrue(
+        &mut self,
+        main_state: &MainState,
+        user_state: &UserState,
+        level: usize,
+        volume_sol: u64,
+        fee: u64,
+        current_timestamp: u64,
+    ) -> Result<u64> {
+        if user_state.referral_chain()[level] != self.referrer {
+            return Ok(0);
+        }
+        let rate = main_state.referral_rates(self.referred_volume)[level];
+        if level == 0 {
+            self.referred_volume = self
+                .referred_volume
+                .checked_add(volume_sol)
+                .ok_or(ThrustAppError::MathOverflow)?;
+        }
+        if !main_state.referral_window_open(user_state, current_timestamp) {
+            return Ok(0);
+        }
+
+        let reward = calculate_trading_fee(rate, fee)?;
+        self.total_earned = self
+            .total_earned
+            .checked_add(reward)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        Ok(reward)
+    }
+}
+
+/// Pays each passed level of the trader's referral chain its reward out of `fee`,
+/// from `payer` into the referrers' vaults. Returns the total paid.
+///
+/// Buys pay from the buyer, sells from the reserve signed by `signer_seeds`, since the
+/// fee was already held back from the seller's proceeds.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_referral_rewards<'info>(
+    main_state: &MainState,
+    user_state: &mut UserState,
+    chain: [Option<&mut Account<'info, ReferrerState>>; ReferrerState::LEVELS],
+    payer: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    system_program: &AccountInfo<'info>,
+    volume_sol: u64,
+    fee: u64,
+) -> Result<u64> {
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    let mut total_reward: u64 = 0;
+    for (level, referrer_state) in chain.into_iter().enumerate() {
+        let Some(referrer_state) = referrer_state else {
+            continue;
+        };
+        let reward = referrer_state.accrue(
+            main_state,
+            user_state,
+            level,
+            volume_sol,
+            fee,
+            current_timestamp,
+        )?;
+        if reward > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: payer.clone(),
+                        to: referrer_state.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                reward,
+            )?;
+            total_reward = total_reward
+                .checked_add(reward)
+                .ok_or(ThrustAppError::MathOverflow)?;
+        }
+    }
+    if total_reward > 0 {
+        user_state.refer_trade_num = user_state
+            .refer_trade_num
+            .checked_add(1)
+            .ok_or(ThrustAppError::MathOverflow)?;
+    }
+    Ok(total_reward)
+}
+
+/// Short human readable code resolving to a referrer, so links can read `/r/moonboy`.
+#[account]
+pub struct ReferralCode {
+    pub code: [u8; 16], // Lowercase letters, digits and `_`, zero padded; also the PDA seed
+    pub referrer: Pubkey,
+}
+
+impl ReferralCode {
+    pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
+    pub const PREFIX_SEED: &'static [u8] = b"referral_code";
+    pub const MIN_LEN: usize = 3;
+
+    pub fn validate_code(code: &[u8; 16]) -> Result<()> {
+        let len = code.iter().take_while(|byte| **byte != 0).count();
+        require!(
+            len >= Self::MIN_LEN
+                && code[..len]
+                    .iter()
+                    .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || *byte == b'_')
+                && code[len..].iter().all(|byte| *byte == 0),
+            ThrustAppError::InvalidReferralCode
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,8 @@
+pub mod upgrade_main_state;
+pub use upgrade_main_state::*;
+
+pub mod upgrade_pool_state;
+pub use upgrade_pool_state::*;
+
+pub mod upgrade_user_state;
+pub use upgrade_user_state::*;
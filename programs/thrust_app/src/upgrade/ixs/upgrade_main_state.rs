@@ -0,0 +1,98 @@
+use anchor_lang::{prelude::*, Discriminator};
+use crate::{
+    constants::{
+        AMM_FEE, CREATOR_FEE_SHARE, GRADUATE_FEE, GRADUATION_BOUNTY_SHARE, MAX_CREATOR_FEE_SHARE,
+        MAX_INTEGRATOR_FEE, MAX_REAL_SOL_THRESHOLD, MAX_TOKEN_DECIMALS, MAX_TOKEN_SUPPLY,
+        MIN_REAL_SOL_THRESHOLD, MIN_TOKEN_SUPPLY, REAL_SOL_THRESHOLD, TOKEN_DECIMALS,
+    },
+    error::ThrustAppError,
+    read_legacy, write_upgraded, FeeVault, MainState, MainStateV1,
+};
+
+/// Upgrades the main state deployed with the launch layout. The account is grown to the
+/// current size, the owner paying the extra rent, the new settings start at the
+/// `init_main_state` defaults and the fee vault is created. Run it first after upgrading
+/// the program: nothing else can load the main state until it has the current layout.
+pub fn upgrade_main_state(ctx: Context<AUpgradeMainState>) -> Result<()> {
+    let account = ctx.accounts.main_state.to_account_info();
+    let space = 8 + MainState::MAX_SIZE;
+    let legacy: MainStateV1 = read_legacy(&account, MainState::DISCRIMINATOR, space)?;
+    require!(
+        legacy.owner == ctx.accounts.owner.key(),
+        ThrustAppError::Unauthorised
+    );
+
+    let state = MainState {
+        initialized: legacy.initialized,
+        owner: legacy.owner,
+        fee_recipient: legacy.fee_recipient,
+        total_token_supply: legacy.total_token_supply,
+        init_virt_base_reserves: legacy.init_virt_base_reserves,
+        init_real_base_reserves: legacy.init_real_base_reserves,
+        init_virt_quote_reserves: legacy.init_virt_quote_reserves,
+        trading_fee: legacy.trading_fee,
+        referral_reward_fee: legacy.referral_reward_fee,
+        referral_window: 0,
+        sol_price: legacy.sol_price,
+        verify_signer_pubkey: legacy.verify_signer_pubkey,
+        real_sol_threshold: REAL_SOL_THRESHOLD,
+        min_real_sol_threshold: MIN_REAL_SOL_THRESHOLD,
+        max_real_sol_threshold: MAX_REAL_SOL_THRESHOLD,
+        graduate_fee: GRADUATE_FEE,
+        token_decimals: TOKEN_DECIMALS,
+        max_token_decimals: MAX_TOKEN_DECIMALS,
+        min_token_supply: MIN_TOKEN_SUPPLY,
+        max_token_supply: MAX_TOKEN_SUPPLY,
+        creator_fee_share: CREATOR_FEE_SHARE,
+        max_creator_fee_share: MAX_CREATOR_FEE_SHARE,
+        fee_tiers: [None; 4],
+        max_integrator_fee: MAX_INTEGRATOR_FEE,
+        fee_splits: [None; 4],
+        referral_tiers: [None; 4],
+        referral_level_fees: [0; 2],
+        amm_program: Pubkey::default(),
+        amm_init_discriminator: [0; 8],
+        amm_init_accounts: [None; 16],
+        built_in_amm: false,
+        amm_fee: AMM_FEE,
+        graduation_bounty_share: GRADUATION_BOUNTY_SHARE,
+    };
+
+    write_upgraded(
+        &account,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        space,
+        &state,
+    )?;
+    msg!("Upgraded mainState");
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AUpgradeMainState<'info> {
+    /// Owner recorded in the deployed main state, pays the extra rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Still in the deployed layout, read and checked by `read_legacy`
+    #[account(
+        mut,
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: UncheckedAccount<'info>,
+
+    /// Fee vault didn't exist in the launch deployment
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [FeeVault::PREFIX_SEED],
+        bump,
+        space = 8 + FeeVault::MAX_SIZE
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    pub system_program: Program<'info, System>,
+}
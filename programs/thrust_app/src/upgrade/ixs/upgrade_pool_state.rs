@@ -0,0 +1,94 @@
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_spl::token::Mint;
+use crate::{
+    constants::{GRADUATE_FEE, REAL_SOL_THRESHOLD, TOTAL_SUPPLY},
+    read_legacy, write_upgraded, CurveType, GraduationMode, PoolState, PoolStateV1, TaxType,
+};
+
+/// Upgrades a pool created with the launch layout, paying the extra rent. Anyone may call
+/// it. The pool keeps the terms it launched with: the constant-product curve, graduating
+/// at the fixed threshold and fee of the time, and no creator fee. Pools have to be
+/// upgraded before they can be traded again.
+pub fn upgrade_pool_state(ctx: Context<AUpgradePoolState>) -> Result<()> {
+    let account = ctx.accounts.pool_state.to_account_info();
+    let space = 8 + PoolState::MAX_SIZE;
+    let legacy: PoolStateV1 = read_legacy(&account, PoolState::DISCRIMINATOR, space)?;
+
+    // Without the holding hook nothing vouches for how long a seller held, so a decay tax
+    // stays at its initial rate instead of trusting the seller
+    let tax_type = match legacy.tax_type {
+        TaxType::DecayTax {
+            initial_tax_rate,
+            duration,
+            ..
+        } => TaxType::FixedTax {
+            rate: initial_tax_rate,
+            duration,
+        },
+        tax_type => tax_type,
+    };
+
+    let state = PoolState {
+        owner: legacy.owner,
+        konst: legacy.konst,
+        mint: legacy.mint,
+        start_trade_timestamp: legacy.start_trade_timestamp,
+        virt_base_reserves: legacy.virt_base_reserves,
+        real_base_reserves: legacy.real_base_reserves,
+        virt_quote_reserves: legacy.virt_quote_reserves,
+        real_quote_reserves: legacy.real_quote_reserves,
+        complete: legacy.complete,
+        withdrawn: legacy.withdrawn,
+        tax_type,
+        tax_start_timestamp: legacy.tax_start_timestamp,
+        waiting_room_state: legacy.waiting_room_state,
+        curve_type: CurveType::ConstantProduct,
+        // Launch pools minted the full supply and kept the virtual part fixed
+        init_real_base_reserves: TOTAL_SUPPLY - legacy.virt_base_reserves,
+        real_sol_threshold: REAL_SOL_THRESHOLD,
+        // Launch pools paid the fee out as they completed
+        graduate_fee: if legacy.complete { 0 } else { GRADUATE_FEE },
+        graduation_mode: GraduationMode::RealSol,
+        trading_fee: None,
+        total_supply: TOTAL_SUPPLY,
+        decimals: ctx.accounts.mint.decimals,
+        holding_hook: false,
+        creator_fee_share: 0,
+        creator_fees_accrued: 0,
+        protocol_fees: 0, // Launch pools paid fees out on every trade
+        amm_pool: None,
+        amm: None,
+        graduation_bounty: 0,
+    };
+
+    write_upgraded(
+        &account,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        space,
+        &state,
+    )
+}
+
+#[derive(Accounts)]
+pub struct AUpgradePoolState<'info> {
+    /// Anyone; pays the extra rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Still in the deployed layout, read and checked by `read_legacy`
+    #[account(
+        mut,
+        seeds = [
+            PoolState::PREFIX_SEED,
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: UncheckedAccount<'info>,
+
+    /// Launch pools were all created on the SPL Token program
+    pub mint: Box<Account<'info, Mint>>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,59 @@
+use anchor_lang::{prelude::*, Discriminator};
+use crate::{read_legacy, write_upgraded, UserState, UserStateV1};
+
+/// Upgrades a user state created with the launch layout, paying the extra rent. Anyone
+/// may call it. An existing referrer's window starts now and the user has no upline, so
+/// only the direct referrer keeps earning. Users have to be upgraded before they can
+/// trade again.
+pub fn upgrade_user_state(ctx: Context<AUpgradeUserState>) -> Result<()> {
+    let account = ctx.accounts.user_state.to_account_info();
+    let space = 8 + UserState::MAX_SIZE;
+    let legacy: UserStateV1 = read_legacy(&account, UserState::DISCRIMINATOR, space)?;
+
+    let referred_at = if legacy.referrer == Pubkey::default() {
+        0
+    } else {
+        Clock::get()?.unix_timestamp as u64
+    };
+    let state = UserState {
+        user: legacy.user,
+        trading_volume_sol: legacy.trading_volume_sol,
+        trading_volume_usd: legacy.trading_volume_usd,
+        trade_count: legacy.trade_count,
+        referrer: legacy.referrer,
+        refer_trade_num: legacy.refer_trade_num,
+        referred_at,
+        upline: [Pubkey::default(); 2],
+    };
+
+    write_upgraded(
+        &account,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        space,
+        &state,
+    )
+}
+
+#[derive(Accounts)]
+pub struct AUpgradeUserState<'info> {
+    /// Anyone; pays the extra rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Wallet the user state belongs to
+    pub user: UncheckedAccount<'info>,
+
+    /// CHECK: Still in the deployed layout, read and checked by `read_legacy`
+    #[account(
+        mut,
+        seeds = [
+            UserState::PREFIX_SEED,
+            user.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_state: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,96 @@
+use anchor_lang::{prelude::*, system_program};
+use crate::{error::ThrustAppError, TaxType, WaitingRoomState};
+
+/// `MainState` as first deployed, before the pool, fee, referral and AMM settings.
+#[derive(AnchorDeserialize)]
+pub struct MainStateV1 {
+    pub initialized: bool,
+    pub owner: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub total_token_supply: u64,
+    pub init_virt_base_reserves: u64,
+    pub init_real_base_reserves: u64,
+    pub init_virt_quote_reserves: u64,
+    pub trading_fee: u64,
+    pub referral_reward_fee: u64,
+    pub referral_trade_limit: u64, // Replaced by `referral_window`
+    pub sol_price: u64,
+    pub verify_signer_pubkey: Pubkey,
+}
+
+/// `PoolState` as first deployed, for constant-product pools with the launch defaults.
+#[derive(AnchorDeserialize)]
+pub struct PoolStateV1 {
+    pub owner: Pubkey,
+    pub konst: u128,
+    pub mint: Pubkey,
+    pub start_trade_timestamp: u64,
+    pub virt_base_reserves: u64,
+    pub real_base_reserves: u64,
+    pub virt_quote_reserves: u64,
+    pub real_quote_reserves: u64,
+    pub complete: bool,
+    pub withdrawn: bool,
+    pub tax_type: TaxType,
+    pub tax_start_timestamp: u64,
+    pub waiting_room_state: WaitingRoomState,
+}
+
+/// `UserState` as first deployed, before the referral window and upline.
+#[derive(AnchorDeserialize)]
+pub struct UserStateV1 {
+    pub user: Pubkey,
+    pub trading_volume_sol: u64,
+    pub trading_volume_usd: u64,
+    pub trade_count: u64,
+    pub referrer: Pubkey,
+    pub refer_trade_num: u64,
+}
+
+/// Reads a program account still in a deployed layout `T`. Accounts already sized for
+/// the current layout (`space` bytes) have nothing to upgrade and are rejected.
+pub fn read_legacy<T: AnchorDeserialize>(
+    account: &AccountInfo,
+    discriminator: [u8; 8],
+    space: usize,
+) -> Result<T> {
+    require!(
+        account.owner == &crate::ID,
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+    require!(account.data_len() < space, ThrustAppError::AccountUpToDate);
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == discriminator,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    T::deserialize(&mut &data[8..]).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+/// Grows `account` to `space` bytes, topping its rent up from `payer`, and writes `state`
+/// over it in the current layout.
+pub fn write_upgraded<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    state: &T,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let top_up = rent.saturating_sub(account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    account.realloc(space, true)?;
+    let mut data = account.try_borrow_mut_data()?;
+    state.try_serialize(&mut &mut data[..])
+}
@@ -1,16 +1,41 @@
 use anchor_lang::prelude::*;
+use crate::error::ThrustAppError;
 
 #[account]
+#[derive(Default)]
 pub struct UserState {
     pub user: Pubkey,
     pub trading_volume_sol: u64,
     pub trading_volume_usd: u64,
     pub trade_count: u64,
     pub referrer: Pubkey,
-    pub refer_trade_num: u64,
+    pub refer_trade_num: u64, // Trades that paid referral rewards
+    pub referred_at: u64,     // When the referrer was bound, starts the referral window
+    pub upline: [Pubkey; 2],  // Referrer's referrer and the one above, when bound
 }
 
 impl UserState {
     pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
     pub const PREFIX_SEED: &'static [u8] = b"user";
+
+    pub fn record_trade(&mut self, volume_sol: u64, volume_usd: u64) -> Result<()> {
+        self.trade_count = self
+            .trade_count
+            .checked_add(1)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        self.trading_volume_sol = self
+            .trading_volume_sol
+            .checked_add(volume_sol)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        self.trading_volume_usd = self
+            .trading_volume_usd
+            .checked_add(volume_usd)
+            .ok_or(ThrustAppError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Referrers earning from this user's trades, direct referrer first.
+    pub fn referral_chain(&self) -> [Pubkey; 3] {
+        [self.referrer, self.upline[0], self.upline[1]]
+    }
 }
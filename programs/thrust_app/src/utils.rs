@@ -1,10 +1,14 @@
 use anchor_lang::solana_program::{hash::hash, secp256k1_recover::secp256k1_recover};
 use anchor_lang::{prelude::*, solana_program::program::invoke};
-use anchor_spl::token::{self, CloseAccount, SyncNative, TokenAccount};
+use anchor_spl::{
+    token::{self, CloseAccount, SyncNative, TokenAccount},
+    token_2022::{self, spl_token_2022},
+    token_interface::{self, TransferChecked},
+};
 use crate::{
     constants::{FEE_PER_DIV, NATIVE_MINT_STR},
     error::ThrustAppError,
-    TaxType, UserState,
+    MainState, TaxType, UserState,
 };
 
 pub fn check_balance_on_pool_creator(ata: &TokenAccount, require_amount: u64) -> bool {
@@ -17,14 +21,26 @@ pub fn check_balance_on_pool_creator(ata: &TokenAccount, require_amount: u64) ->
     true
 }
 
-pub fn calculate_trading_fee(fee: u64, amount: u64) -> u64 {
-    (amount as u128)
+pub fn calculate_trading_fee(fee: u64, amount: u64) -> Result<u64> {
+    let fee_amount = (amount as u128)
         .checked_mul(fee.into())
-        .unwrap()
-        .checked_div(FEE_PER_DIV)
-        .unwrap()
-        .checked_div(100)
-        .unwrap() as u64
+        .ok_or(ThrustAppError::MathOverflow)?
+        / FEE_PER_DIV
+        / 100;
+    u64::try_from(fee_amount).map_err(|_| ThrustAppError::MathOverflow.into())
+}
+
+// SOL amount (in lamports) converted with the stored SOL price
+pub fn calculate_volume_usd(sol_amount: u64, sol_price: u64) -> Result<u64> {
+    let volume_usd = (sol_amount as u128) * (sol_price as u128) / 1_000_000_000;
+    u64::try_from(volume_usd).map_err(|_| ThrustAppError::MathOverflow.into())
+}
+
+// Inverse of `calculate_volume_usd`: lamports worth `usd_amount` at `sol_price`
+pub fn calculate_sol_from_usd(usd_amount: u64, sol_price: u64) -> Result<u64> {
+    require!(sol_price > 0, ThrustAppError::SolPriceNotSet);
+    let sol_amount = (usd_amount as u128) * 1_000_000_000 / (sol_price as u128);
+    u64::try_from(sol_amount).map_err(|_| ThrustAppError::MathOverflow.into())
 }
 
 pub fn calculate_tax_rate(
@@ -36,21 +52,15 @@ pub fn calculate_tax_rate(
     seller_balance: u64,
     main_trading_fee_rate: u64,
     last_received_time: u64,
-) -> u64 {
-    match tax_type {
+) -> Result<u64> {
+    let rate = match tax_type {
         TaxType::HigherSellTax {
             threshold_percentage,
             higher_tax_rate,
             standard_tax_rate,
             ..
         } => {
-            let threshold = (total_supply as u128)
-                .checked_mul(*threshold_percentage as u128)
-                .unwrap()
-                .checked_div(FEE_PER_DIV)
-                .unwrap()
-                .checked_div(100)
-                .unwrap() as u64;
+            let threshold = calculate_trading_fee(*threshold_percentage, total_supply)?;
 
             if seller_balance >= threshold {
                 *higher_tax_rate
@@ -66,9 +76,8 @@ pub fn calculate_tax_rate(
         } => {
             let holding_time_days = current_timestamp
                 .checked_sub(last_received_time)
-                .unwrap()
-                .checked_div(86400)
-                .unwrap() as u64;
+                .ok_or(ThrustAppError::MathOverflow)?
+                / 86400;
 
             let applicable_rate = reduction_tiers
                 .iter()
@@ -78,11 +87,12 @@ pub fn calculate_tax_rate(
                 .max()
                 .unwrap_or(*min_tax_rate);
 
-            applicable_rate.clamp(*min_tax_rate, *initial_tax_rate)
+            applicable_rate.min(*initial_tax_rate).max(*min_tax_rate)
         }
         TaxType::FixedTax { rate, .. } => *rate,
         _ => main_trading_fee_rate,
-    }
+    };
+    Ok(rate)
 }
 
 pub fn close_token_account<'a>(
@@ -136,6 +146,31 @@ pub fn sync_native_amount<'a>(
     Ok(())
 }
 
+/// `transfer_checked` that also resolves the transfer hook accounts of Token-2022 mints.
+/// The hook program, its validation account and the extra metas it lists are taken from
+/// the context's remaining accounts.
+pub fn transfer_tokens<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    if ctx.program.key() != token_2022::ID || ctx.remaining_accounts.is_empty() {
+        return token_interface::transfer_checked(ctx, amount, decimals);
+    }
+    spl_token_2022::onchain::invoke_transfer_checked(
+        &token_2022::ID,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
 pub fn verify_signed_message(signature: &[u8; 65], signer_pubkey: &Pubkey) -> bool {
     let message_hash = hash(&[]).to_bytes();
 
@@ -4,23 +4,43 @@ import assert from "assert";
 import * as web3 from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 
 import { sha256 } from "js-sha256";
 import secp256k1 from "secp256k1";
 import type { ThrustApp } from "../target/types/thrust_app";
+import type { HoldingHook } from "../target/types/holding_hook";
+import type { MockAmm } from "../target/types/mock_amm";
 
 const MAIN_STATE_SEED = "main_4";
 const signer = anchor.Wallet.local().payer;
+// Trades are signed with secp256k1, the program stores the hash of the recovered key
+const verifySigner = new web3.PublicKey(
+  sha256.array(secp256k1.publicKeyCreate(signer.secretKey.slice(0, 32), false).slice(1))
+);
 const TOKEN_PROGRAM = TOKEN_PROGRAM_ID;
 const ASSOCIATED_TOKEN_PROGRAM = ASSOCIATED_TOKEN_PROGRAM_ID;
 const METADATA_PROGRAM = new web3.PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
 let mint = web3.Keypair.generate();
+const hookMint = web3.Keypair.generate();
 
 // Configure the client to use the local cluster
 anchor.setProvider(anchor.AnchorProvider.env());
 const program = anchor.workspace.ThrustApp as anchor.Program<ThrustApp>;
+const holdingHook = anchor.workspace.HoldingHook as anchor.Program<HoldingHook>;
+const mockAmm = anchor.workspace.MockAmm as anchor.Program<MockAmm>;
+
+const [feeVaultPDA] = web3.PublicKey.findProgramAddressSync(
+  [Buffer.from("fee_vault")],
+  program.programId
+);
+const creatorVaultOf = (mint: web3.PublicKey) =>
+  web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("creator_vault"), mint.toBuffer()],
+    program.programId
+  )[0];
 
 describe("Test Initialize", () => {
   it("init", async () => {
@@ -41,8 +61,9 @@ describe("Test Initialize", () => {
       .initMainState()
       .accounts({
         owner: signer.publicKey,
-        verifySignerPubkey: signer.publicKey,
+        verifySignerPubkey: verifySigner,
         mainState: mainStatePDA[0],
+        feeVault: feeVaultPDA,
         systemProgram: web3.SystemProgram.programId,
       })
       .rpc();
@@ -69,16 +90,66 @@ describe("Test Initialize", () => {
         feeRecipient: signer.publicKey,
         tradingFee: new BN(1000),
         referralRewardFee: new BN(10000),
-        referralTradeLimit: new BN(100),
+        referralWindow: new BN(0),
         totalTokenSupply: new BN(1_000_000_000 * 1000_000),
         initRealBaseReserves: new BN(800_000_000 * 1000_000),
         initVirtBaseReserves: new BN(200_000_000 * 1000_000),
         initVirtQuoteReserves: new BN(24 * 1000_000_000),
         solPrice: new BN(130_000_000_000),
+        realSolThreshold: null,
+        minRealSolThreshold: null,
+        maxRealSolThreshold: null,
+        graduateFee: null,
+        tokenDecimals: null,
+        maxTokenDecimals: null,
+        minTokenSupply: null,
+        maxTokenSupply: null,
+        creatorFeeShare: null,
+        maxCreatorFeeShare: null,
+        // Lower fees once a wallet traded $1M, then $10M, of volume
+        feeTiers: [
+          { minVolumeUsd: new BN("1000000000000000"), tradingFee: new BN(800) },
+          { minVolumeUsd: new BN("10000000000000000"), tradingFee: new BN(500) },
+          null,
+          null,
+        ],
+        maxIntegratorFee: null,
+        feeSplits: null,
+        referralTiers: [
+          { minReferredVolume: new BN(100 * 1_000_000_000), rewardFee: new BN(15000) },
+          null,
+          null,
+          null,
+        ],
+        referralLevelFees: [new BN(3000), new BN(1000)],
+        // Completed pools migrate to the local mock AMM, in its account order
+        ammProgram: mockAmm.programId,
+        ammInitDiscriminator: Array.from(sha256.array("global:initialize_pool").slice(0, 8)),
+        ammInitAccounts: [
+          { payer: {} },
+          { reserve: {} },
+          { poolAuthority: {} },
+          { lpOwner: {} },
+          { baseMint: {} },
+          { ammPool: {} },
+          { ammLpMint: {} },
+          { ammBaseVault: {} },
+          { reserveBaseAccount: {} },
+          { poolLpAccount: {} },
+          { baseTokenProgram: {} },
+          { lpTokenProgram: {} },
+          { associatedTokenProgram: {} },
+          { systemProgram: {} },
+          null,
+          null,
+        ],
+        builtInAmm: null,
+        ammFee: new BN(300),
+        graduationBountyShare: null,
       })
       .accounts({
         owner: signer.publicKey,
-        verifySignerPubkey: signer.publicKey,
+        verifySignerPubkey: verifySigner,
         mainState: mainStatePDA[0],
       })
       .rpc();
@@ -108,15 +179,31 @@ describe("Test Initialize", () => {
       1000,
       "tradingFee was not updated"
     );
+    assert.equal(
+      deserializedAccountData.feeTiers.filter((tier) => tier !== null).length,
+      2,
+      "feeTiers were not updated"
+    );
     assert.equal(
       deserializedAccountData.referralRewardFee,
       10000,
       "referralRewardFee was not updated"
     );
     assert.equal(
-      deserializedAccountData.referralTradeLimit,
-      100,
-      "referralTradeLimit was not updated"
+      deserializedAccountData.referralWindow,
+      0,
+      "referralWindow was not updated"
+    );
+    assert.deepEqual(
+      deserializedAccountData.referralLevelFees.map((fee) => fee.toNumber()),
+      [3000, 1000],
+      "referralLevelFees were not updated"
+    );
+    assert.equal(deserializedAccountData.ammFee, 300, "ammFee was not updated");
+    assert.equal(
+      deserializedAccountData.ammProgram.toBase58(),
+      mockAmm.programId.toBase58(),
+      "ammProgram was not updated"
     );
     assert.equal(
       deserializedAccountData.totalTokenSupply,
@@ -148,7 +235,7 @@ describe("Test Initialize", () => {
       .updateSolPrice(new BN(160_000_000_000))
       .accounts({
         owner: signer.publicKey,
-        verifySignerPubkey: signer.publicKey,
+        verifySignerPubkey: verifySigner,
         mainState: mainStatePDA[0],
       })
       .rpc();
@@ -164,6 +251,90 @@ describe("Test Initialize", () => {
       "sol price set failed"
     );
   });
+  it("create launch profile", async () => {
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const name = Buffer.alloc(32);
+    name.write("standard");
+    const [launchProfile] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("launch_profile"), name],
+      program.programId
+    );
+    try {
+      await program.account.launchProfile.fetch(launchProfile);
+      console.log("launch profile already created");
+      return;
+    } catch { }
+    const tx = await program.methods
+      .createLaunchProfile({
+        name: Array.from(name),
+        enabled: true,
+        curveType: { constantProduct: {} },
+        initRealBaseReserves: new BN(800_000_000 * 1000_000),
+        initVirtQuoteReserves: new BN(30 * 1000_000_000),
+        realSolThreshold: new BN(85 * 1000_000_000),
+        graduateFee: new BN(4 * 1000_000_000),
+        tradingFee: new BN(800),
+        allowedTaxTypes: 0b1111,
+        waitingRoomConfig: null,
+      })
+      .accounts({
+        owner: signer.publicKey,
+        mainState: mainStatePDA[0],
+        launchProfile,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    console.log(`Use 'solana confirm -v ${tx}' to see the logs`);
+
+    const deserializedAccountData = await program.account.launchProfile.fetch(launchProfile);
+    assert.equal(
+      deserializedAccountData.realSolThreshold.toNumber(),
+      85_000_000_000,
+      "launch profile was not created"
+    );
+  });
+  it("set and remove fee override", async () => {
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const wallet = web3.Keypair.generate().publicKey;
+    const [feeOverride] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_override"), wallet.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .setFeeOverride({ wallet, tradingFee: new BN(200), taxExempt: true })
+      .accounts({
+        owner: signer.publicKey,
+        mainState: mainStatePDA[0],
+        feeOverride,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const deserializedAccountData = await program.account.feeOverride.fetch(feeOverride);
+    assert.equal(deserializedAccountData.wallet.toBase58(), wallet.toBase58());
+    assert.equal(deserializedAccountData.tradingFee.toNumber(), 200, "fee override was not set");
+    assert.equal(deserializedAccountData.taxExempt, true);
+
+    await program.methods
+      .removeFeeOverride()
+      .accounts({
+        owner: signer.publicKey,
+        mainState: mainStatePDA[0],
+        feeOverride,
+      })
+      .rpc({ commitment: "confirmed" });
+    assert.equal(
+      await program.provider.connection.getAccountInfo(feeOverride),
+      null,
+      "fee override was not removed"
+    );
+  });
 });
 describe("Test Create Pool", () => {
   it("create pool", async () => {
@@ -232,7 +403,16 @@ describe("Test Create Pool", () => {
               maxParticipants: 500
             } 
           },
-        }
+        },
+        curveType: { constantProduct: {} },
+        realSolThreshold: null,
+        graduationMode: { realSol: {} },
+        startMarketCapUsd: null,
+        totalSupply: null,
+        decimals: null,
+        initialBuySol: null,
+        minTokensOut: new BN(0),
+        creatorFeeShare: new BN(20_000),
       })
       .accounts({
         mint: mint.publicKey,
@@ -241,8 +421,16 @@ describe("Test Create Pool", () => {
         mainState: mainStatePDA[0],
         poolState,
         userState,
-        referrer: web3.PublicKey.default,
+        referrerState: null,
+        level2ReferrerState: null,
+        level3ReferrerState: null,
+        referrerUserState: null,
+        referralCode: null,
         reserverBaseAta: reserveAta,
+        creatorVault: creatorVaultOf(mint.publicKey),
+        reservePda: null,
+        creatorBaseAta: null,
+        feeOverride: null,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM,
         tokenProgram: TOKEN_PROGRAM,
         metadataProgram: METADATA_PROGRAM,
@@ -253,6 +441,199 @@ describe("Test Create Pool", () => {
     console.log("Created pool ->", poolState.toBase58());
     console.log(`Use 'solana confirm -v ${txHash}' to see the logs`);
   });
+  it("create token-2022 pool", async () => {
+    const mint2022 = web3.Keypair.generate();
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), mint2022.publicKey.toBuffer()],
+      program.programId
+    );
+    const [userState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+    const [reserveAta] = web3.PublicKey.findProgramAddressSync(
+      [
+        poolState.toBuffer(),
+        TOKEN_2022_PROGRAM_ID.toBuffer(),
+        mint2022.publicKey.toBuffer(),
+      ],
+      ASSOCIATED_TOKEN_PROGRAM
+    );
+    const [reservePda] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reserve"), mint2022.publicKey.toBuffer()],
+      program.programId
+    );
+    const [creatorBaseAta] = web3.PublicKey.findProgramAddressSync(
+      [
+        signer.publicKey.toBuffer(),
+        TOKEN_2022_PROGRAM_ID.toBuffer(),
+        mint2022.publicKey.toBuffer(),
+      ],
+      ASSOCIATED_TOKEN_PROGRAM
+    );
+    const txHash = await program.methods
+      .createPool2022({
+        mintName: "bimple the token-2022 bird",
+        mintSymbol: "BBB2",
+        mintUri: "https://cryptologos.cc/logos/solana-sol-logo.svg",
+        tradeStartTime: new BN(0),
+        taxType: { disabled: {} },
+        waitingRoomConfig: null,
+        curveType: { constantProduct: {} },
+        realSolThreshold: null,
+        graduationMode: { realSol: {} },
+        startMarketCapUsd: null,
+        totalSupply: null,
+        decimals: 9,
+        initialBuySol: new BN(100_000_000),
+        minTokensOut: new BN(1),
+        creatorFeeShare: null,
+      })
+      .accounts({
+        mainState: mainStatePDA[0],
+        mint: mint2022.publicKey,
+        creator: signer.publicKey,
+        poolState,
+        userState,
+        referrerState: null,
+        level2ReferrerState: null,
+        level3ReferrerState: null,
+        referrerUserState: null,
+        referralCode: null,
+        reserverBaseAta: reserveAta,
+        creatorVault: creatorVaultOf(mint2022.publicKey),
+        reservePda,
+        creatorBaseAta,
+        feeOverride: null,
+        holdingHookProgram: null,
+        extraAccountMetaList: null,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([mint2022])
+      .rpc({ commitment: "confirmed" });
+    console.log(`Use 'solana confirm -v ${txHash}' to see the logs`);
+
+    const pool = await program.account.poolState.fetch(poolState);
+    assert.equal(pool.decimals, 9, "token-2022 pool decimals mismatch");
+    assert.equal(pool.holdingHook, false, "non decay tax pool shouldn't use the hook");
+    assert(pool.realQuoteReserves.gtn(0), "initial buy should fill the reserve");
+    const creatorBalance =
+      await program.provider.connection.getTokenAccountBalance(creatorBaseAta);
+    assert(Number(creatorBalance.value.amount) > 0, "creator should receive the initial buy");
+  });
+
+  it("create decay tax pool with holding hook", async () => {
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), hookMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const [userState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+    const [reserveAta] = web3.PublicKey.findProgramAddressSync(
+      [
+        poolState.toBuffer(),
+        TOKEN_2022_PROGRAM_ID.toBuffer(),
+        hookMint.publicKey.toBuffer(),
+      ],
+      ASSOCIATED_TOKEN_PROGRAM
+    );
+    const [extraAccountMetaList] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("extra-account-metas"), hookMint.publicKey.toBuffer()],
+      holdingHook.programId
+    );
+    const txHash = await program.methods
+      .createPool2022({
+        mintName: "bimple the holding bird",
+        mintSymbol: "BBBH",
+        mintUri: "https://cryptologos.cc/logos/solana-sol-logo.svg",
+        tradeStartTime: new BN(0),
+        taxType: {
+          decayTax: {
+            initialTaxRate: new BN(10_000),
+            reductionTiers: [
+              { daysHeld: new BN(7), taxRate: new BN(5_000) },
+              null,
+              null,
+              null,
+            ],
+            minTaxRate: new BN(1_000),
+            duration: { lifetime: {} },
+          },
+        },
+        waitingRoomConfig: null,
+        curveType: { constantProduct: {} },
+        realSolThreshold: null,
+        graduationMode: { realSol: {} },
+        startMarketCapUsd: null,
+        totalSupply: null,
+        decimals: null,
+        initialBuySol: null,
+        minTokensOut: new BN(0),
+        creatorFeeShare: null,
+      })
+      .accounts({
+        mainState: mainStatePDA[0],
+        mint: hookMint.publicKey,
+        creator: signer.publicKey,
+        poolState,
+        userState,
+        referrerState: null,
+        level2ReferrerState: null,
+        level3ReferrerState: null,
+        referrerUserState: null,
+        referralCode: null,
+        launchProfile: null,
+        reserverBaseAta: reserveAta,
+        creatorVault: creatorVaultOf(hookMint.publicKey),
+        reservePda: null,
+        creatorBaseAta: null,
+        feeOverride: null,
+        holdingHookProgram: holdingHook.programId,
+        extraAccountMetaList,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([hookMint])
+      .rpc({ commitment: "confirmed" });
+    console.log(`Use 'solana confirm -v ${txHash}' to see the logs`);
+
+    const pool = await program.account.poolState.fetch(poolState);
+    assert.equal(pool.holdingHook, true, "decay tax pool should use the hook");
+
+    // Holding time is only tracked once the holder's record exists, and starts now
+    const [holderState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("holder"), reserveAta.toBuffer()],
+      holdingHook.programId
+    );
+    await holdingHook.methods
+      .initHolderState()
+      .accounts({
+        payer: signer.publicKey,
+        tokenAccount: reserveAta,
+        holderState,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc({ commitment: "confirmed" });
+    const holder = await holdingHook.account.holderState.fetch(holderState);
+    assert.ok(holder.mint.equals(hookMint.publicKey), "holder state mint mismatch");
+    assert.ok(
+      holder.lastReceivedTime.toNumber() > 0,
+      "holder state should start at the current time"
+    );
+  });
 });
 describe("Test Buy and Sell", () => {
   const mintPublickey = mint.publicKey;
@@ -322,19 +703,28 @@ describe("Test Buy and Sell", () => {
 
     const builder = program.methods.buy({
       amount: new BN(100000000),
+      minTokensOut: new BN(0),
       signature: Array.from(serializedSignature),
+      integratorFee: new BN(0),
     })
       .accounts({
         buyer: signer.publicKey,
         mainState: mainStatePDA[0],
-        feeRecipient: deserializedAccountData.feeRecipient,
         userState,
-        referrer: web3.PublicKey.default,
+        referrerState: null,
+        level2ReferrerState: null,
+        level3ReferrerState: null,
+        referrerUserState: null,
+        referralCode: null,
+        feeOverride: null,
+        integrator: null,
         poolState,
         mint: mintPublickey,
         buyerBaseAta,
         reservePda,
+        creatorVault: creatorVaultOf(mintPublickey),
         reserverBaseAta: reserveAta,
+        holderState: null,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM,
         tokenProgram: TOKEN_PROGRAM,
         systemProgram: web3.SystemProgram.programId,
@@ -358,6 +748,98 @@ describe("Test Buy and Sell", () => {
     }
   });
 
+  it("buy exact out", async () => {
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    try {
+      await program.account.poolState.fetch(poolState.toBase58());
+    } catch {
+      return;
+    }
+    const [reservePda] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reserve"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const [userState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+    const [reserveAta] = web3.PublicKey.findProgramAddressSync(
+      [
+        poolState.toBuffer(),
+        TOKEN_PROGRAM.toBuffer(),
+        mintPublickey.toBuffer(),
+      ],
+      ASSOCIATED_TOKEN_PROGRAM
+    );
+    const [buyerBaseAta] = web3.PublicKey.findProgramAddressSync(
+      [
+        signer.publicKey.toBuffer(),
+        TOKEN_PROGRAM.toBuffer(),
+        mintPublickey.toBuffer(),
+      ],
+      ASSOCIATED_TOKEN_PROGRAM
+    );
+    const deserializedAccountData = await program.account.mainState.fetch(
+      mainStatePDA[0].toBase58()
+    );
+
+    const message = new Uint8Array([]);
+    const messageHash = new Uint8Array(sha256.array(message));
+    const signature = secp256k1.ecdsaSign(messageHash, signer.secretKey.slice(0, 32));
+    const serializedSignature = new Uint8Array([...signature.signature, signature.recid]);
+
+    const tokenAmount = new BN(1_000_000_000_000); // 1M tokens
+    const balanceBefore = new BN(
+      (await program.provider.connection.getTokenAccountBalance(buyerBaseAta)).value.amount
+    );
+    const txHash = await program.methods
+      .buyExactOut({
+        amount: tokenAmount,
+        maxSolIn: new BN(1_000_000_000),
+        signature: Array.from(serializedSignature),
+        integratorFee: new BN(0),
+      })
+      .accounts({
+        buyer: signer.publicKey,
+        mainState: mainStatePDA[0],
+        userState,
+        referrerState: null,
+        level2ReferrerState: null,
+        level3ReferrerState: null,
+        referrerUserState: null,
+        referralCode: null,
+        feeOverride: null,
+        integrator: null,
+        poolState,
+        mint: mintPublickey,
+        buyerBaseAta,
+        reservePda,
+        creatorVault: creatorVaultOf(mintPublickey),
+        reserverBaseAta: reserveAta,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM,
+        tokenProgram: TOKEN_PROGRAM,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc({ commitment: "confirmed" });
+    console.log(`Use 'solana confirm -v ${txHash}' to see the logs`);
+
+    const balanceAfter = new BN(
+      (await program.provider.connection.getTokenAccountBalance(buyerBaseAta, "confirmed")).value.amount
+    );
+    assert.equal(
+      balanceAfter.sub(balanceBefore).toString(),
+      tokenAmount.toString(),
+      "buy exact out received wrong amount"
+    );
+  });
+
   it("sell", async () => {
     const mainStatePDA = web3.PublicKey.findProgramAddressSync(
       [Buffer.from(MAIN_STATE_SEED)],
@@ -425,19 +907,27 @@ describe("Test Buy and Sell", () => {
     const builder = program.methods
       .sell({
         amount: new BN(3_000_000_000_000),
+        minSolOut: new BN(0),
         signature: Array.from(serializedSignature),
-        lastReceivedTime: new BN(Date.now() / 1000 - 86400)
+        lastReceivedTime: new BN(Date.now() / 1000 - 86400),
+        integratorFee: new BN(0),
       })
       .accounts({
         seller: signer.publicKey,
         mainState: mainStatePDA[0],
-        feeRecipient: deserializedAccountData.feeRecipient,
         userState,
-        referrer: web3.PublicKey.default,
+        referrerState: null,
+        level2ReferrerState: null,
+        level3ReferrerState: null,
+        referrerUserState: null,
+        referralCode: null,
+        feeOverride: null,
+        integrator: null,
         poolState,
         mint: mintPublickey,
         sellerBaseAta,
         reservePda,
+        creatorVault: creatorVaultOf(mintPublickey),
         reserverBaseAta: reserveAta,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM,
         tokenProgram: TOKEN_PROGRAM,
@@ -461,6 +951,714 @@ describe("Test Buy and Sell", () => {
       console.log(simRes.value);
     }
   });
+
+  it("referral rewards", async () => {
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const [reservePda] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reserve"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const [userState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+    const [reserveAta] = web3.PublicKey.findProgramAddressSync(
+      [poolState.toBuffer(), TOKEN_PROGRAM.toBuffer(), mintPublickey.toBuffer()],
+      ASSOCIATED_TOKEN_PROGRAM
+    );
+    const [buyerBaseAta] = web3.PublicKey.findProgramAddressSync(
+      [signer.publicKey.toBuffer(), TOKEN_PROGRAM.toBuffer(), mintPublickey.toBuffer()],
+      ASSOCIATED_TOKEN_PROGRAM
+    );
+    const user = await program.account.userState.fetch(userState);
+    if (!user.referrer.equals(web3.PublicKey.default)) {
+      console.log("signer already has a referrer");
+      return;
+    }
+
+    const referrer = web3.Keypair.generate();
+    const airdrop = await program.provider.connection.requestAirdrop(
+      referrer.publicKey,
+      web3.LAMPORTS_PER_SOL
+    );
+    await program.provider.connection.confirmTransaction(airdrop, "confirmed");
+    const [referrerState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("referrer"), referrer.publicKey.toBuffer()],
+      program.programId
+    );
+    const [referrerUserState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), referrer.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .registerReferrer()
+      .accounts({
+        referrer: referrer.publicKey,
+        referrerState,
+        userState: referrerUserState,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([referrer])
+      .rpc({ commitment: "confirmed" });
+
+    const codeOf = (text: string) => {
+      const code = Buffer.alloc(16);
+      code.write(text);
+      return code;
+    };
+    const referralCodePda = (code: Buffer) =>
+      web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("referral_code"), code],
+        program.programId
+      )[0];
+    try {
+      const code = codeOf("MoonBoy");
+      await program.methods
+        .registerReferralCode(Array.from(code))
+        .accounts({
+          referrer: referrer.publicKey,
+          referrerState,
+          referralCode: referralCodePda(code),
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([referrer])
+        .rpc();
+      assert.fail("uppercase codes should be rejected");
+    } catch (err) {
+      assert(`${err}`.includes("InvalidReferralCode"), `${err}`);
+    }
+    const code = codeOf(`moon_${referrer.publicKey.toBase58().slice(0, 6).toLowerCase()}`);
+    const referralCode = referralCodePda(code);
+    await program.methods
+      .registerReferralCode(Array.from(code))
+      .accounts({
+        referrer: referrer.publicKey,
+        referrerState,
+        referralCode,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([referrer])
+      .rpc({ commitment: "confirmed" });
+    const registered = await program.account.referralCode.fetch(referralCode);
+    assert.equal(registered.referrer.toBase58(), referrer.publicKey.toBase58());
+
+    const message = new Uint8Array([]);
+    const messageHash = new Uint8Array(sha256.array(message));
+    const signature = secp256k1.ecdsaSign(messageHash, signer.secretKey.slice(0, 32));
+    const serializedSignature = new Uint8Array([...signature.signature, signature.recid]);
+    const buyInput = {
+      amount: new BN(100000000),
+      minTokensOut: new BN(0),
+      signature: Array.from(serializedSignature),
+      integratorFee: new BN(0),
+    };
+    const buyAccounts = {
+      buyer: signer.publicKey,
+      mainState: mainStatePDA[0],
+      userState,
+      referrerState,
+      level2ReferrerState: null,
+      level3ReferrerState: null,
+      referrerUserState,
+      referralCode,
+      feeOverride: null,
+      integrator: null,
+      poolState,
+      mint: mintPublickey,
+      buyerBaseAta,
+      reservePda,
+      creatorVault: creatorVaultOf(mintPublickey),
+      reserverBaseAta: reserveAta,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM,
+      tokenProgram: TOKEN_PROGRAM,
+      systemProgram: web3.SystemProgram.programId,
+    };
+    try {
+      await program.methods
+        .buy(buyInput)
+        .accounts({ ...buyAccounts, referrerUserState: null })
+        .rpc();
+      assert.fail("binding a referrer should need the referrer's user state");
+    } catch (err) {
+      assert(`${err}`.includes("ReferrerUserStateRequired"), `${err}`);
+    }
+    await program.methods
+      .buy(buyInput)
+      .accounts(buyAccounts)
+      .rpc({ commitment: "confirmed" });
+
+    const bound = await program.account.userState.fetch(userState);
+    assert.equal(bound.referrer.toBase58(), referrer.publicKey.toBase58(), "referrer was not bound");
+    const earned = await program.account.referrerState.fetch(referrerState);
+    assert.equal(earned.referees.toNumber(), 1);
+    assert(earned.totalEarned.gtn(0), "the referral should earn a reward");
+
+    const balanceBefore = await program.provider.connection.getBalance(referrer.publicKey);
+    await program.methods
+      .claimReferralRewards()
+      .accounts({ referrer: referrer.publicKey, referrerState })
+      .signers([referrer])
+      .rpc({ commitment: "confirmed" });
+    const claimed = await program.account.referrerState.fetch(referrerState);
+    assert.equal(claimed.totalClaimed.toString(), earned.totalEarned.toString());
+    const balanceAfter = await program.provider.connection.getBalance(referrer.publicKey);
+    assert.equal(balanceAfter - balanceBefore + 5000, earned.totalEarned.toNumber());
+  });
+
+  it("claim creator fees", async () => {
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const creatorVault = creatorVaultOf(mintPublickey);
+    const pool = await program.account.poolState.fetch(poolState);
+    assert(pool.creatorFeesAccrued.gtn(0), "trades should accrue creator fees");
+
+    const vaultBefore = await program.provider.connection.getBalance(creatorVault);
+    const txHash = await program.methods
+      .claimCreatorFees()
+      .accounts({
+        owner: signer.publicKey,
+        poolState,
+        creatorVault,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc({ commitment: "confirmed" });
+    console.log(`Use 'solana confirm -v ${txHash}' to see the logs`);
+
+    const vaultAfter = await program.provider.connection.getBalance(creatorVault);
+    assert(vaultAfter < vaultBefore, "claim should empty the creator vault");
+  });
+
+  it("sweep protocol fees", async () => {
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const [reservePda] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reserve"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const pool = await program.account.poolState.fetch(poolState);
+    assert(pool.protocolFees.gtn(0), "trades should accrue protocol fees");
+
+    const reserveBefore = await program.provider.connection.getBalance(reservePda);
+    const txHash = await program.methods
+      .sweepProtocolFees()
+      .accounts({
+        mainState: mainStatePDA[0],
+        feeVault: feeVaultPDA,
+        poolState,
+        reservePda,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc({ commitment: "confirmed" });
+    console.log(`Use 'solana confirm -v ${txHash}' to see the logs`);
+
+    const reserveAfter = await program.provider.connection.getBalance(reservePda);
+    assert.equal(
+      reserveBefore - reserveAfter,
+      pool.protocolFees.toNumber(),
+      "sweep should move exactly the accrued fees"
+    );
+    const swept = await program.account.poolState.fetch(poolState);
+    assert(swept.protocolFees.eqn(0), "accrued fees should be reset");
+  });
+
+  it("distribute fees", async () => {
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const mainState = await program.account.mainState.fetch(mainStatePDA[0]);
+    const vaultBefore = await program.provider.connection.getBalance(feeVaultPDA);
+    const recipientBefore = await program.provider.connection.getBalance(mainState.feeRecipient);
+
+    // No fee split is configured, so everything goes to the fee recipient
+    const txHash = await program.methods
+      .distributeFees()
+      .accounts({
+        mainState: mainStatePDA[0],
+        feeVault: feeVaultPDA,
+      })
+      .remainingAccounts([
+        { pubkey: mainState.feeRecipient, isSigner: false, isWritable: true },
+      ])
+      .rpc({ commitment: "confirmed" });
+    console.log(`Use 'solana confirm -v ${txHash}' to see the logs`);
+
+    const vaultAfter = await program.provider.connection.getBalance(feeVaultPDA);
+    const recipientAfter = await program.provider.connection.getBalance(mainState.feeRecipient);
+    assert(vaultAfter < vaultBefore, "the vault should be paid out");
+    // The fee recipient also paid the 5000 lamports transaction fee
+    assert.equal(
+      recipientAfter - recipientBefore + 5000,
+      vaultBefore - vaultAfter,
+      "the fee recipient should receive the vault's balance"
+    );
+    const vault = await program.account.feeVault.fetch(feeVaultPDA);
+    assert.equal(vault.totalDistributed.toNumber(), vaultBefore - vaultAfter);
+  });
+
+  it("migrate rejects an incomplete pool", async () => {
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const [reservePda] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reserve"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const ataOf = (owner: web3.PublicKey, mint: web3.PublicKey) =>
+      web3.PublicKey.findProgramAddressSync(
+        [owner.toBuffer(), TOKEN_PROGRAM.toBuffer(), mint.toBuffer()],
+        ASSOCIATED_TOKEN_PROGRAM
+      )[0];
+    const [ammPool] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("amm_pool"), mintPublickey.toBuffer()],
+      mockAmm.programId
+    );
+    const [ammLpMint] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), ammPool.toBuffer()],
+      mockAmm.programId
+    );
+
+    try {
+      await program.methods
+        .migrate()
+        .accounts({
+          owner: signer.publicKey,
+          mainState: mainStatePDA[0],
+          feeVault: feeVaultPDA,
+          poolState,
+          mint: mintPublickey,
+          reservePda,
+          reserverBaseAta: ataOf(poolState, mintPublickey),
+          ammPool,
+          ammLpMint,
+          ammBaseVault: ataOf(ammPool, mintPublickey),
+          poolLpAccount: ataOf(poolState, ammLpMint),
+          ammProgram: mockAmm.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM,
+          tokenProgram: TOKEN_PROGRAM,
+          lpTokenProgram: TOKEN_PROGRAM,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("pools still on the curve can't be migrated");
+    } catch (err) {
+      assert(`${err}`.includes("BondingCurveIncomplete"), `${err}`);
+    }
+    const pool = await program.account.poolState.fetch(poolState);
+    assert.equal(pool.ammPool, null, "no AMM pool should be recorded");
+  });
+
+  it("graduate can be called by anyone once the pool completes", async () => {
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const [reservePda] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reserve"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const ataOf = (owner: web3.PublicKey, mint: web3.PublicKey) =>
+      web3.PublicKey.findProgramAddressSync(
+        [owner.toBuffer(), TOKEN_PROGRAM.toBuffer(), mint.toBuffer()],
+        ASSOCIATED_TOKEN_PROGRAM
+      )[0];
+    const [ammPool] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("amm_pool"), mintPublickey.toBuffer()],
+      mockAmm.programId
+    );
+    const [ammLpMint] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), ammPool.toBuffer()],
+      mockAmm.programId
+    );
+
+    // Any wallet may crank the graduation, not just the owner
+    const keeper = web3.Keypair.generate();
+    const airdrop = await program.provider.connection.requestAirdrop(
+      keeper.publicKey,
+      web3.LAMPORTS_PER_SOL
+    );
+    await program.provider.connection.confirmTransaction(airdrop, "confirmed");
+    const accounts = {
+      keeper: keeper.publicKey,
+      mainState: mainStatePDA[0],
+      feeVault: feeVaultPDA,
+      poolState,
+      mint: mintPublickey,
+      reservePda,
+      reserverBaseAta: ataOf(poolState, mintPublickey),
+      ammPool,
+      ammLpMint,
+      ammBaseVault: ataOf(ammPool, mintPublickey),
+      poolLpAccount: ataOf(poolState, ammLpMint),
+      ammProgram: mockAmm.programId,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM,
+      tokenProgram: TOKEN_PROGRAM,
+      lpTokenProgram: TOKEN_PROGRAM,
+      systemProgram: web3.SystemProgram.programId,
+    };
+
+    // Keepers can only graduate to the AMM configured in the main state
+    try {
+      await program.methods
+        .graduate()
+        .accounts({ ...accounts, ammProgram: holdingHook.programId })
+        .signers([keeper])
+        .rpc();
+      assert.fail("graduating to another program should fail");
+    } catch (err) {
+      assert(`${err}`.includes("InvalidAmmProgram"), `${err}`);
+    }
+
+    try {
+      await program.methods.graduate().accounts(accounts).signers([keeper]).rpc();
+      assert.fail("pools still on the curve can't graduate");
+    } catch (err) {
+      assert(`${err}`.includes("BondingCurveIncomplete"), `${err}`);
+    }
+    const pool = await program.account.poolState.fetch(poolState);
+    assert.equal(pool.ammPool, null, "no AMM pool should be recorded");
+    assert(pool.graduationBounty.eqn(0), "no bounty is held before the pool completes");
+  });
+
+  it("swap rejects a pool still on the curve", async () => {
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const [reservePda] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reserve"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const ataOf = (owner: web3.PublicKey) =>
+      web3.PublicKey.findProgramAddressSync(
+        [owner.toBuffer(), TOKEN_PROGRAM.toBuffer(), mintPublickey.toBuffer()],
+        ASSOCIATED_TOKEN_PROGRAM
+      )[0];
+
+    try {
+      await program.methods
+        .swap({ amountIn: new BN(100000000), minAmountOut: new BN(0), isBuy: true })
+        .accounts({
+          user: signer.publicKey,
+          poolState,
+          mint: mintPublickey,
+          reservePda,
+          reserverBaseAta: ataOf(poolState),
+          userBaseAta: ataOf(signer.publicKey),
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM,
+          tokenProgram: TOKEN_PROGRAM,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("pools without a built-in AMM can't be swapped");
+    } catch (err) {
+      assert(`${err}`.includes("AmmNotActive"), `${err}`);
+    }
+    const pool = await program.account.poolState.fetch(poolState);
+    assert.equal(pool.amm, null, "the curve should not have turned into an AMM");
+  });
+});
+
+describe("Test Quotes", () => {
+  const mintPublickey = mint.publicKey;
+  it("quote buy, quote sell and pool info", async () => {
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const [userState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+    const [userBaseAta] = web3.PublicKey.findProgramAddressSync(
+      [
+        signer.publicKey.toBuffer(),
+        TOKEN_PROGRAM.toBuffer(),
+        mintPublickey.toBuffer(),
+      ],
+      ASSOCIATED_TOKEN_PROGRAM
+    );
+    const accounts = {
+      mainState: mainStatePDA[0],
+      poolState,
+      userState,
+      userBaseAta,
+      holderState: null,
+      referrerState: null,
+      feeOverride: null,
+    };
+
+    const info = await program.methods.poolInfo().accounts(accounts).view();
+    const poolStateData = await program.account.poolState.fetch(poolState);
+    assert.equal(
+      info.realQuoteReserves.toString(),
+      poolStateData.realQuoteReserves.toString(),
+      "pool info reserves mismatch"
+    );
+
+    const buyQuote = await program.methods
+      .quoteBuy({
+        amount: new BN(100000000),
+        minTokensOut: new BN(0),
+        signature: new Array(65).fill(0),
+        integratorFee: new BN(500),
+      })
+      .accounts(accounts)
+      .view();
+    assert(buyQuote.tokenAmount.gtn(0), "quote buy returned no tokens");
+    assert(buyQuote.integratorFee.gtn(0), "quote buy should charge the integrator fee");
+    assert(buyQuote.solAmount.lten(100000000), "integrator fee should come out of the amount");
+    assert(buyQuote.price.gt(info.price), "buy should move the price up");
+
+    const sellQuote = await program.methods
+      .quoteSell({
+        amount: buyQuote.tokenAmount,
+        minSolOut: new BN(0),
+        signature: new Array(65).fill(0),
+        lastReceivedTime: new BN(Date.now() / 1000 - 86400),
+        integratorFee: new BN(0),
+      })
+      .accounts(accounts)
+      .view();
+    assert(sellQuote.solAmount.lt(buyQuote.solAmount), "round trip should not be profitable");
+  });
+
+  it("quote sell ignores a backdated receive time", async () => {
+    const mainStatePDA = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), hookMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const [reservePda] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reserve"), hookMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const [userState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+    const [reserveAta] = web3.PublicKey.findProgramAddressSync(
+      [
+        poolState.toBuffer(),
+        TOKEN_2022_PROGRAM_ID.toBuffer(),
+        hookMint.publicKey.toBuffer(),
+      ],
+      ASSOCIATED_TOKEN_PROGRAM
+    );
+    const [buyerBaseAta] = web3.PublicKey.findProgramAddressSync(
+      [
+        signer.publicKey.toBuffer(),
+        TOKEN_2022_PROGRAM_ID.toBuffer(),
+        hookMint.publicKey.toBuffer(),
+      ],
+      ASSOCIATED_TOKEN_PROGRAM
+    );
+    const [extraAccountMetaList] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("extra-account-metas"), hookMint.publicKey.toBuffer()],
+      holdingHook.programId
+    );
+    const [holderState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("holder"), buyerBaseAta.toBuffer()],
+      holdingHook.programId
+    );
+
+    // Buy into the decay tax pool so there is something to sell
+    await program.methods
+      .buy({
+        amount: new BN(100000000),
+        minTokensOut: new BN(0),
+        signature: new Array(65).fill(0),
+        integratorFee: new BN(0),
+      })
+      .accounts({
+        buyer: signer.publicKey,
+        mainState: mainStatePDA[0],
+        userState,
+        referrerState: null,
+        level2ReferrerState: null,
+        level3ReferrerState: null,
+        referrerUserState: null,
+        referralCode: null,
+        feeOverride: null,
+        integrator: null,
+        poolState,
+        mint: hookMint.publicKey,
+        buyerBaseAta,
+        reservePda,
+        creatorVault: creatorVaultOf(hookMint.publicKey),
+        reserverBaseAta: reserveAta,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .remainingAccounts([
+        { pubkey: holdingHook.programId, isSigner: false, isWritable: false },
+        { pubkey: extraAccountMetaList, isSigner: false, isWritable: false },
+        { pubkey: holderState, isSigner: false, isWritable: true },
+      ])
+      .rpc({ commitment: "confirmed" });
+    await holdingHook.methods
+      .initHolderState()
+      .accounts({
+        payer: signer.publicKey,
+        tokenAccount: buyerBaseAta,
+        holderState,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const quoteSell = (lastReceivedTime: BN) =>
+      program.methods
+        .quoteSell({
+          amount: new BN(1_000_000),
+          minSolOut: new BN(0),
+          signature: new Array(65).fill(0),
+          lastReceivedTime,
+          integratorFee: new BN(0),
+        })
+        .accounts({
+          mainState: mainStatePDA[0],
+          poolState,
+          userState,
+          userBaseAta: buyerBaseAta,
+          holderState,
+          referrerState: null,
+          feeOverride: null,
+        })
+        .view();
+
+    // Claiming to have held for a month doesn't reach the 7 day tier
+    const honest = await quoteSell(new BN(Date.now() / 1000));
+    const backdated = await quoteSell(new BN(Date.now() / 1000 - 30 * 86400));
+    assert.equal(
+      backdated.tax.toString(),
+      honest.tax.toString(),
+      "a backdated receive time should not change the tax"
+    );
+    assert.equal(
+      backdated.solAmount.toString(),
+      honest.solAmount.toString(),
+      "a backdated receive time should not change the proceeds"
+    );
+  });
+});
+
+describe("Test Upgrade", () => {
+  const mintPublickey = mint.publicKey;
+
+  it("upgrade rejects accounts already on the current layout", async () => {
+    const [mainState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(MAIN_STATE_SEED)],
+      program.programId
+    );
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), mintPublickey.toBuffer()],
+      program.programId
+    );
+    const [userState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), signer.publicKey.toBuffer()],
+      program.programId
+    );
+    const upgrades = [
+      program.methods
+        .upgradeMainState()
+        .accounts({
+          owner: signer.publicKey,
+          mainState,
+          feeVault: feeVaultPDA,
+          systemProgram: web3.SystemProgram.programId,
+        }),
+      program.methods
+        .upgradePoolState()
+        .accounts({
+          payer: signer.publicKey,
+          poolState,
+          mint: mintPublickey,
+          systemProgram: web3.SystemProgram.programId,
+        }),
+      program.methods
+        .upgradeUserState()
+        .accounts({
+          payer: signer.publicKey,
+          user: signer.publicKey,
+          userState,
+          systemProgram: web3.SystemProgram.programId,
+        }),
+    ];
+    for (const upgrade of upgrades) {
+      try {
+        await upgrade.rpc();
+        assert.fail("current accounts have nothing to upgrade");
+      } catch (err) {
+        assert(`${err}`.includes("AccountUpToDate"), `${err}`);
+      }
+    }
+  });
+
+  it("upgrade puts a legacy decay tax pool on a fixed tax", async () => {
+    // Loaded by the validator from tests/fixtures, see Anchor.toml
+    const legacyMint = new web3.PublicKey("CxTKTdDsW8s6y4GTdtN5uRN4Xt7avytgfoSYbN7K12yn");
+    const [poolState] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), legacyMint.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .upgradePoolState()
+      .accounts({
+        payer: signer.publicKey,
+        poolState,
+        mint: legacyMint,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    // Without the holding hook the decay tax would trust the seller's holding time
+    const pool = await program.account.poolState.fetch(poolState);
+    assert.equal(pool.holdingHook, false, "legacy pools have no holding hook");
+    assert.ok(pool.taxType.fixedTax, "decay tax should become a fixed tax");
+    assert.equal(
+      pool.taxType.fixedTax.rate.toNumber(),
+      10_000,
+      "fixed tax should keep the initial decay rate"
+    );
+    assert.ok(pool.taxType.fixedTax.duration.lifetime, "tax duration should be kept");
+    assert.equal(
+      pool.initRealBaseReserves.toString(),
+      "800000000000000",
+      "creation reserves should be backfilled"
+    );
+  });
 });
 
 // can't test withdraw because bonding curve is not completed, pool has buy limit for each wallet, so can't buy all amount